@@ -0,0 +1,28 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use mpz_circuits::types::Value;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let value = Value::Array(vec![Value::U128(0); 10000]);
+
+    c.bench_function("value_type::array_u128_10000", |bench| {
+        bench.iter(|| black_box(black_box(&value).value_type()));
+    });
+
+    c.bench_function("cached_type::array_u128_10000", |bench| {
+        bench.iter(|| black_box(black_box(&value).cached_type()));
+    });
+
+    let nested = Value::Array(vec![Value::Array(vec![Value::U128(0); 100]); 100]);
+
+    c.bench_function("value_type::array_array_u128_100_100", |bench| {
+        bench.iter(|| black_box(black_box(&nested).value_type()));
+    });
+
+    c.bench_function("cached_type::array_array_u128_100_100", |bench| {
+        bench.iter(|| black_box(black_box(&nested).cached_type()));
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -2,10 +2,14 @@ use itybity::{BitIterable, IntoBits};
 
 use crate::{
     components::{Feed, Gate, Node},
-    types::{BinaryLength, BinaryRepr, ToBinaryRepr, ValueType},
+    types::{BinaryLength, BinaryRepr, ToBinaryRepr, TypeError, ValueType},
     Circuit, Tracer,
 };
-use std::{cell::RefCell, collections::HashMap, mem::discriminant};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    mem::discriminant,
+};
 
 /// An error that can occur when building a circuit.
 #[derive(Debug, thiserror::Error)]
@@ -15,6 +19,15 @@ pub enum BuilderError {
     MissingWire(usize),
     #[error("error appending circuit: {0}")]
     AppendError(String),
+    #[error("type error: {0}")]
+    TypeError(#[from] TypeError),
+    #[error(
+        "dangling nodes: unused inputs {unused_inputs:?}, undriven outputs {undriven_outputs:?}"
+    )]
+    DanglingNodes {
+        unused_inputs: Vec<usize>,
+        undriven_outputs: Vec<usize>,
+    },
 }
 
 /// A circuit builder.
@@ -76,6 +89,30 @@ impl CircuitBuilder {
         Tracer::new(&self.state, value)
     }
 
+    /// Adds a new input to the circuit of the provided type, labelled with a name
+    ///
+    /// The name can be queried on the built circuit with [`Circuit::input_name`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the input.
+    ///
+    /// # Returns
+    ///
+    /// The binary encoded form of the input.
+    pub fn add_input_named<T: ToBinaryRepr + BinaryLength>(
+        &self,
+        name: &str,
+    ) -> Tracer<'_, T::Repr> {
+        let input = self.add_input::<T>();
+
+        let mut state = self.state.borrow_mut();
+        let idx = state.inputs.len() - 1;
+        state.input_names.insert(idx, name.to_string());
+
+        input
+    }
+
     /// Adds a new input to the circuit of the provided type
     ///
     /// # Arguments
@@ -141,12 +178,48 @@ impl CircuitBuilder {
     }
 
     /// Adds a new output to the circuit
+    ///
+    /// The same value may be added as an output more than once, in which case each
+    /// output refers to the same nodes in the built circuit.
     pub fn add_output(&self, value: impl Into<BinaryRepr>) {
         let mut state = self.state.borrow_mut();
 
         state.outputs.push(value.into());
     }
 
+    /// Adds a new output to the circuit, labelled with a name
+    ///
+    /// The name can be queried on the built circuit with [`Circuit::output_name`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the output.
+    /// * `value` - The output.
+    pub fn add_output_named(&self, name: &str, value: impl Into<BinaryRepr>) {
+        let mut state = self.state.borrow_mut();
+
+        state.outputs.push(value.into());
+        let idx = state.outputs.len() - 1;
+        state.output_names.insert(idx, name.to_string());
+    }
+
+    /// Adds a new output to the circuit of the provided type
+    ///
+    /// # Arguments
+    ///
+    /// * `ty` - The type of the output.
+    /// * `nodes` - The nodes of the output, in lsb0 order.
+    pub fn add_output_typed(
+        &self,
+        ty: &ValueType,
+        nodes: &[Node<Feed>],
+    ) -> Result<(), BuilderError> {
+        let value = BinaryRepr::from_nodes(ty, nodes)?;
+        self.add_output(value);
+
+        Ok(())
+    }
+
     /// Returns a tracer for a constant value
     pub fn get_constant<T: ToBinaryRepr + BitIterable>(&self, value: T) -> Tracer<'_, T::Repr> {
         let mut state = self.state.borrow_mut();
@@ -173,10 +246,64 @@ impl CircuitBuilder {
         self.state.borrow_mut().append(circ, builder_inputs)
     }
 
+    /// Appends a copy of a circuit for each element of an array
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The array to map over
+    /// * `circ` - The circuit to apply to each element, which must have exactly one input
+    ///   and one output
+    ///
+    /// # Returns
+    ///
+    /// An array of the outputs of each copy of the circuit
+    pub fn map_array(
+        &self,
+        input: &BinaryRepr,
+        circ: &Circuit,
+    ) -> Result<BinaryRepr, BuilderError> {
+        let BinaryRepr::Array(elems) = input else {
+            return Err(BuilderError::AppendError(format!(
+                "Input to map over must be an array, got {}",
+                input
+            )));
+        };
+
+        if circ.inputs().len() != 1 || circ.outputs().len() != 1 {
+            return Err(BuilderError::AppendError(format!(
+                "Mapped circuit must have one input and one output, got {} inputs and {} outputs",
+                circ.inputs().len(),
+                circ.outputs().len()
+            )));
+        }
+
+        let mut state = self.state.borrow_mut();
+        let outputs = elems
+            .iter()
+            .map(|elem| {
+                state
+                    .append(circ, std::slice::from_ref(elem))
+                    .map(|mut outputs| outputs.remove(0))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(BinaryRepr::Array(outputs))
+    }
+
     /// Builds the circuit
     pub fn build(self) -> Result<Circuit, BuilderError> {
         self.state.into_inner().build()
     }
+
+    /// Builds the circuit, first checking that every input is connected to a gate or
+    /// an output, and that every output is driven by an input, a gate or a constant.
+    ///
+    /// The node ids in the returned error are those assigned by the builder.
+    pub fn build_checked(self) -> Result<Circuit, BuilderError> {
+        let state = self.state.into_inner();
+        state.check_connected()?;
+        state.build()
+    }
 }
 
 /// The internal state of the [`CircuitBuilder`]
@@ -186,6 +313,8 @@ pub struct BuilderState {
     inputs: Vec<BinaryRepr>,
     outputs: Vec<BinaryRepr>,
     gates: Vec<Gate>,
+    input_names: HashMap<usize, String>,
+    output_names: HashMap<usize, String>,
 
     and_count: usize,
     xor_count: usize,
@@ -199,6 +328,8 @@ impl Default for BuilderState {
             inputs: vec![],
             outputs: vec![],
             gates: vec![],
+            input_names: HashMap::default(),
+            output_names: HashMap::default(),
             and_count: 0,
             xor_count: 0,
         }
@@ -429,6 +560,53 @@ impl BuilderState {
         Ok(outputs)
     }
 
+    /// Checks that every input feed is consumed by a gate or an output, and that every
+    /// output feed is driven.
+    pub(crate) fn check_connected(&self) -> Result<(), BuilderError> {
+        let mut consumed = HashSet::new();
+        let mut driven: HashSet<usize> =
+            HashSet::from([self.get_const_zero().id(), self.get_const_one().id()]);
+
+        for input in &self.inputs {
+            driven.extend(input.iter().map(|node| node.id()));
+        }
+
+        for gate in &self.gates {
+            consumed.insert(gate.x().id());
+            if let Some(y) = gate.y() {
+                consumed.insert(y.id());
+            }
+            driven.insert(gate.z().id());
+        }
+
+        let mut undriven_outputs = Vec::new();
+        for output in &self.outputs {
+            for node in output.iter() {
+                consumed.insert(node.id());
+                if !driven.contains(&node.id()) {
+                    undriven_outputs.push(node.id());
+                }
+            }
+        }
+
+        let unused_inputs: Vec<_> = self
+            .inputs
+            .iter()
+            .flat_map(|input| input.iter())
+            .map(|node| node.id())
+            .filter(|id| !consumed.contains(id))
+            .collect();
+
+        if unused_inputs.is_empty() && undriven_outputs.is_empty() {
+            Ok(())
+        } else {
+            Err(BuilderError::DanglingNodes {
+                unused_inputs,
+                undriven_outputs,
+            })
+        }
+    }
+
     /// Builds the circuit.
     pub(crate) fn build(mut self) -> Result<Circuit, BuilderError> {
         // Shift all the node ids to the left by 2 to eliminate
@@ -439,10 +617,19 @@ impl BuilderState {
             .iter_mut()
             .for_each(|output| output.shift_left(2));
 
+        let input_names = (0..self.inputs.len())
+            .map(|idx| self.input_names.remove(&idx))
+            .collect();
+        let output_names = (0..self.outputs.len())
+            .map(|idx| self.output_names.remove(&idx))
+            .collect();
+
         Ok(Circuit {
             inputs: self.inputs,
             outputs: self.outputs,
             gates: self.gates,
+            input_names,
+            output_names,
             feed_count: self.feed_id,
             and_count: self.and_count,
             xor_count: self.xor_count,
@@ -454,7 +641,7 @@ impl BuilderState {
 mod test {
     use mpz_circuits_macros::evaluate;
 
-    use crate::ops::WrappingAdd;
+    use crate::{ops::WrappingAdd, types::Value};
 
     use super::*;
 
@@ -510,4 +697,156 @@ mod test {
         // a + (a + b) = 2a + b
         assert_eq!(d, 3u8);
     }
+
+    #[test]
+    fn test_map_array() {
+        let inc = {
+            let builder = CircuitBuilder::new();
+            let a = builder.add_input::<u8>();
+            let one = builder.get_constant(1u8);
+            builder.add_output(a.wrapping_add(one));
+            builder.build().unwrap()
+        };
+
+        let builder = CircuitBuilder::new();
+        let input: BinaryRepr = builder.add_input::<[u8; 4]>().into();
+
+        let output = builder.map_array(&input, &inc).unwrap();
+        assert_eq!(output.value_type(), ValueType::new_array::<u8>(4));
+
+        assert!(matches!(
+            builder.map_array(&inc.inputs()[0], &inc),
+            Err(BuilderError::AppendError(_))
+        ));
+
+        builder.add_output(output);
+
+        let circ = builder.build().unwrap();
+
+        let output = circ.evaluate(&[Value::from([1u8, 2, 3, 255])]).unwrap();
+        assert_eq!(output, vec![Value::from([2u8, 3, 4, 0])]);
+    }
+
+    #[test]
+    fn test_add_output_typed() {
+        let builder = CircuitBuilder::new();
+
+        let a: BinaryRepr = builder.add_input::<[u8; 4]>().into();
+        let nodes: Vec<_> = a.iter().copied().collect();
+
+        builder.add_output_typed(&ValueType::U32, &nodes).unwrap();
+        assert!(matches!(
+            builder.add_output_typed(&ValueType::U64, &nodes),
+            Err(BuilderError::TypeError(TypeError::InvalidLength { .. }))
+        ));
+
+        let circ = builder.build().unwrap();
+
+        assert_eq!(circ.outputs().len(), 1);
+        assert_eq!(circ.outputs()[0].value_type(), ValueType::U32);
+
+        let output: u32 = circ
+            .evaluate(&[[1u8, 2, 3, 4].into()])
+            .unwrap()
+            .pop()
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(output, u32::from_le_bytes([1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_add_output_twice() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<u8>();
+        let c = a.wrapping_add(b);
+
+        builder.add_output(c);
+        builder.add_output(c);
+
+        let circ = builder.build().unwrap();
+
+        assert_eq!(circ.outputs().len(), 2);
+        assert!(circ.outputs()[0].iter().eq(circ.outputs()[1].iter()));
+
+        let outputs = circ.evaluate(&[Value::U8(1), Value::U8(2)]).unwrap();
+        assert_eq!(outputs, vec![Value::U8(3), Value::U8(3)]);
+    }
+
+    #[test]
+    fn test_named_io() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_input_named::<u8>("a");
+        let b = builder.add_input::<u8>();
+        let c = a.wrapping_add(b);
+
+        builder.add_output_named("sum", c);
+        builder.add_output(c);
+
+        let circ = builder.build().unwrap();
+
+        assert_eq!(circ.input_name(0), Some("a"));
+        assert_eq!(circ.input_name(1), None);
+        assert_eq!(circ.input_name(2), None);
+        assert_eq!(circ.output_name(0), Some("sum"));
+        assert_eq!(circ.output_name(1), None);
+
+        let circ = circ.reverse_inputs();
+        assert_eq!(circ.input_name(0), None);
+        assert_eq!(circ.input_name(1), Some("a"));
+    }
+
+    #[test]
+    fn test_build_checked() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<u8>();
+
+        builder.add_output(a.wrapping_add(b));
+
+        assert!(builder.build_checked().is_ok());
+    }
+
+    #[test]
+    fn test_build_checked_unused_input() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_input::<u8>();
+        let _b = builder.add_input::<u8>();
+
+        builder.add_output(a);
+
+        let err = builder.build_checked().unwrap_err();
+
+        assert!(matches!(
+            err,
+            BuilderError::DanglingNodes { unused_inputs, undriven_outputs }
+                if unused_inputs == (10..18).collect::<Vec<_>>() && undriven_outputs.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_build_checked_undriven_output() {
+        let other = CircuitBuilder::new();
+        let _ = other.add_input::<u8>();
+        let x = other.add_input::<u8>();
+
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u8>();
+
+        builder.add_output(a);
+        builder.add_output(x);
+
+        let err = builder.build_checked().unwrap_err();
+
+        assert!(matches!(
+            err,
+            BuilderError::DanglingNodes { unused_inputs, undriven_outputs }
+                if unused_inputs.is_empty() && undriven_outputs == (10..18).collect::<Vec<_>>()
+        ));
+    }
 }
@@ -1,8 +1,8 @@
 use itybity::IntoBits;
 
 use crate::{
-    components::Gate,
-    types::{BinaryRepr, TypeError, Value},
+    components::{Gate, GateType},
+    types::{BinaryRepr, TypeError, Value, ValueType},
 };
 
 /// An error that can occur when performing operations with a circuit.
@@ -24,23 +24,58 @@ pub struct Circuit {
     pub(crate) inputs: Vec<BinaryRepr>,
     pub(crate) outputs: Vec<BinaryRepr>,
     pub(crate) gates: Vec<Gate>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) input_names: Vec<Option<String>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) output_names: Vec<Option<String>>,
     pub(crate) feed_count: usize,
 
     pub(crate) and_count: usize,
     pub(crate) xor_count: usize,
 }
 
+/// Statistics about a circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitStats {
+    /// The number of AND gates.
+    pub and_count: usize,
+    /// The number of XOR gates.
+    pub xor_count: usize,
+    /// The number of inverter gates.
+    pub inv_count: usize,
+    /// The number of feeds.
+    pub feed_count: usize,
+    /// The multiplicative depth, i.e. the largest number of AND gates on any path through
+    /// the circuit.
+    pub and_depth: usize,
+}
+
 impl Circuit {
     /// Returns a reference to the inputs of the circuit.
     pub fn inputs(&self) -> &[BinaryRepr] {
         &self.inputs
     }
 
+    /// Returns the types of the inputs of the circuit, in order.
+    pub fn input_types(&self) -> Vec<ValueType> {
+        self.inputs.iter().map(|input| input.value_type()).collect()
+    }
+
+    /// Returns the name of the input at the given index, if it was given one.
+    pub fn input_name(&self, idx: usize) -> Option<&str> {
+        self.input_names.get(idx)?.as_deref()
+    }
+
     /// Returns a reference to the outputs of the circuit.
     pub fn outputs(&self) -> &[BinaryRepr] {
         &self.outputs
     }
 
+    /// Returns the name of the output at the given index, if it was given one.
+    pub fn output_name(&self, idx: usize) -> Option<&str> {
+        self.output_names.get(idx)?.as_deref()
+    }
+
     /// Returns a reference to the gates of the circuit.
     pub fn gates(&self) -> &[Gate] {
         &self.gates
@@ -61,9 +96,45 @@ impl Circuit {
         self.xor_count
     }
 
+    /// Returns statistics about the circuit.
+    pub fn stats(&self) -> CircuitStats {
+        let mut depth = vec![0usize; self.feed_count];
+        let mut inv_count = 0;
+        let mut and_depth = 0;
+
+        for gate in self.gates.iter() {
+            let d = match gate.y() {
+                Some(y) => depth[gate.x().id].max(depth[y.id]),
+                None => depth[gate.x().id],
+            };
+
+            let d = match gate.gate_type() {
+                GateType::And => d + 1,
+                GateType::Inv => {
+                    inv_count += 1;
+                    d
+                }
+                GateType::Xor => d,
+            };
+
+            depth[gate.z().id] = d;
+            and_depth = and_depth.max(d);
+        }
+
+        CircuitStats {
+            and_count: self.and_count,
+            xor_count: self.xor_count,
+            inv_count,
+            feed_count: self.feed_count,
+            and_depth,
+        }
+    }
+
     /// Reverses the order of the inputs.
     pub fn reverse_inputs(mut self) -> Self {
         self.inputs.reverse();
+        self.input_names.resize(self.inputs.len(), None);
+        self.input_names.reverse();
         self
     }
 
@@ -88,6 +159,8 @@ impl Circuit {
     /// Reverses the order of the outputs.
     pub fn reverse_outputs(mut self) -> Self {
         self.outputs.reverse();
+        self.output_names.resize(self.outputs.len(), None);
+        self.output_names.reverse();
         self
     }
 
@@ -129,7 +202,8 @@ impl Circuit {
         let mut feeds: Vec<Option<bool>> = vec![None; self.feed_count];
 
         for (input, value) in self.inputs.iter().zip(values) {
-            if input.value_type() != value.value_type() {
+            // Names are transparent, a named value is laid out like its inner value.
+            if !input.value_type().matches(&value.value_type()) || input.len() != value.bit_len() {
                 return Err(TypeError::UnexpectedType {
                     expected: input.value_type(),
                     actual: value.value_type(),
@@ -166,7 +240,6 @@ impl Circuit {
         let outputs = self
             .outputs
             .iter()
-            .cloned()
             .map(|output| {
                 let bits: Vec<bool> = output
                     .iter()
@@ -221,4 +294,52 @@ mod tests {
 
         assert_eq!(out, 3u8);
     }
+
+    #[test]
+    fn test_evaluate_named() {
+        let circ = build_adder();
+
+        let a = Value::Named {
+            name: "a".to_string(),
+            inner: Box::new(Value::U8(1)),
+        };
+        let out = circ.evaluate(&[a, Value::U8(2)]).unwrap();
+
+        assert_eq!(out, vec![Value::U8(3)]);
+        assert!(circ.evaluate(&[Value::U16(1), Value::U8(2)]).is_err());
+    }
+
+    #[test]
+    fn test_stats() {
+        let circ = build_adder();
+        let stats = circ.stats();
+
+        assert_eq!(stats.and_count, circ.and_count());
+        assert_eq!(stats.xor_count, circ.xor_count());
+        assert_eq!(stats.feed_count, circ.feed_count());
+        assert_eq!(
+            stats.and_count + stats.xor_count + stats.inv_count,
+            circ.gates().len()
+        );
+        assert!(stats.and_count > 0);
+        assert!(stats.and_depth > 0 && stats.and_depth <= stats.and_count);
+    }
+
+    #[test]
+    fn test_input_types() {
+        let builder = CircuitBuilder::new();
+
+        let a = builder.add_input::<u8>();
+        let b = builder.add_input::<[u16; 2]>();
+
+        builder.add_output(a);
+        builder.add_output(b);
+
+        let circ = builder.build().unwrap();
+
+        assert_eq!(
+            circ.input_types(),
+            vec![ValueType::U8, ValueType::new_array::<u16>(2)]
+        );
+    }
 }
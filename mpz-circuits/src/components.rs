@@ -1,4 +1,4 @@
-use std::{fmt::Display, marker::PhantomData};
+use core::{fmt::Display, marker::PhantomData};
 
 /// A binary logic gate.
 #[derive(Debug, Clone, Copy)]
@@ -95,6 +95,11 @@ pub enum GateType {
 /// A feed in a circuit.
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct Feed;
 
 /// A sink in a circuit.
@@ -105,19 +110,24 @@ pub struct Sink;
 /// A node in a circuit.
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
 pub struct Node<T> {
     pub(crate) id: usize,
-    _pd: std::marker::PhantomData<T>,
+    _pd: core::marker::PhantomData<T>,
 }
 
 impl Display for Node<Feed> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "Feed({})", self.id)
     }
 }
 
 impl Display for Node<Sink> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "Sink({})", self.id)
     }
 }
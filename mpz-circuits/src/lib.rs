@@ -1,29 +1,54 @@
 //! This crate provides types for representing computation as binary circuits.
+//!
+//! # Features
+//!
+//! The `std` feature is enabled by default. Without it the crate is `no_std` and only
+//! provides the [`types`] module, which requires an allocator.
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs, unreachable_pub, unused_must_use)]
 
+extern crate alloc;
 extern crate self as mpz_circuits;
 
+#[cfg(feature = "std")]
 mod builder;
+#[cfg(feature = "std")]
 mod circuit;
+#[cfg(feature = "std")]
 pub mod circuits;
 pub(crate) mod components;
+#[cfg(feature = "std")]
 pub mod ops;
 #[cfg(feature = "parse")]
 mod parse;
+#[cfg(feature = "std")]
 mod tracer;
 pub mod types;
 
+#[cfg(feature = "std")]
 #[doc(hidden)]
 pub use builder::BuilderState;
+#[cfg(feature = "std")]
 pub use builder::{BuilderError, CircuitBuilder};
-pub use circuit::{Circuit, CircuitError};
+#[cfg(feature = "std")]
+pub use circuit::{Circuit, CircuitError, CircuitStats};
+#[cfg(feature = "rkyv")]
+#[doc(hidden)]
+pub use components::{ArchivedFeed, ArchivedNode, FeedResolver, NodeResolver};
 #[doc(hidden)]
 pub use components::{Feed, Node, Sink};
 pub use components::{Gate, GateType};
+#[cfg(feature = "std")]
 pub use tracer::Tracer;
 
+#[cfg(feature = "std")]
 pub use once_cell;
 
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::vec;
+}
+
 /// An attribute macro that can be applied to a function to automatically convert
 /// it into a circuit.
 ///
@@ -123,6 +148,7 @@ pub use once_cell;
 /// This preserves the original function, which can be used for testing.
 ///
 /// This suffix can be overriden by passing the `suffix = "new_suffix"` argument to the macro.
+#[cfg(feature = "std")]
 pub use mpz_circuits_macros::trace;
 
 /// Evaluates a circuit and attempts to coerce the output into the specified return type
@@ -154,6 +180,7 @@ pub use mpz_circuits_macros::trace;
 ///
 /// assert_eq!(output, 1u8 + 2u8);
 /// ```
+#[cfg(feature = "std")]
 pub use mpz_circuits_macros::evaluate;
 
 /// Helper macro for testing that a circuit evaluates to the expected value.
@@ -179,4 +206,5 @@ pub use mpz_circuits_macros::evaluate;
 ///
 /// test_circ!(circ, wrapping_add, fn(1u8, 2u8) -> u8);
 /// ```
+#[cfg(feature = "std")]
 pub use mpz_circuits_macros::test_circ;
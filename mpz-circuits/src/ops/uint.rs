@@ -1,7 +1,7 @@
 use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Shr};
 
 use crate::{
-    types::{BinaryRepr, U128, U16, U32, U64, U8},
+    types::{BinaryRepr, Endian, U128, U16, U32, U64, U8},
     Tracer,
 };
 
@@ -390,6 +390,22 @@ impl_neg_uint!(U128);
 macro_rules! impl_convert_bytes {
     ($ty:ident, $len:expr) => {
         impl<'a> Tracer<'a, $ty> {
+            /// Create a value from its representation as a byte array in the given byte order.
+            pub fn from_bytes(bytes: [Tracer<'a, U8>; $len], endian: Endian) -> Tracer<'a, $ty> {
+                let state = bytes[0].state;
+                Tracer::new(
+                    state,
+                    $ty::from_bytes(bytes.map(|byte| byte.to_inner()), endian),
+                )
+            }
+
+            /// Returns the representation of this type as a byte array in the given byte order.
+            pub fn to_bytes(self, endian: Endian) -> [Tracer<'a, U8>; $len] {
+                self.value
+                    .to_bytes(endian)
+                    .map(|value| Tracer::new(self.state, value))
+            }
+
             /// Create a value from its representation as a byte array in big endian.
             pub fn from_be_bytes(bytes: [Tracer<'a, U8>; $len]) -> Tracer<'a, $ty> {
                 let state = bytes[0].state;
@@ -33,15 +33,6 @@ where
     }
 }
 
-impl<'a, const N: usize, T> From<[Tracer<'a, T>; N]> for BinaryRepr
-where
-    T: Into<BinaryRepr>,
-{
-    fn from(tracer: [Tracer<'a, T>; N]) -> Self {
-        BinaryRepr::Array(tracer.into_iter().map(|tracer| tracer.into()).collect())
-    }
-}
-
 impl<'a, T> From<Vec<Tracer<'a, T>>> for BinaryRepr
 where
     T: Into<BinaryRepr>,
@@ -1,25 +1,170 @@
 //! Types for encoding other types as binary values.
 
-use std::{
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
+    cmp::Ordering,
     fmt::{self, Display, Formatter},
     ops::{BitXor, Index},
 };
+#[cfg(feature = "std")]
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
 
 use crate::components::{Feed, Node};
 use itybity::{FromBitIterator, IntoBits};
 use rand::Rng;
 
 /// An error related to binary type conversions.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug)]
 #[allow(missing_docs)]
 pub enum TypeError {
-    #[error("Invalid binary representation length: expected: {expected}, actual: {actual}")]
-    InvalidLength { expected: usize, actual: usize },
-    #[error("Unexpected type, expected: {expected}, actual: {actual}")]
+    /// The binary representation has the wrong length.
+    InvalidLength {
+        expected: usize,
+        actual: usize,
+        /// The path of array indices leading to the element with the invalid length,
+        /// which is empty if the error is not from a nested element.
+        path: Vec<usize>,
+    },
     UnexpectedType {
         expected: ValueType,
         actual: ValueType,
     },
+    ZeroLengthArray,
+    NotInteger {
+        actual: ValueType,
+    },
+    InvalidRepr {
+        path: Vec<usize>,
+        expected: ValueType,
+        actual: ValueType,
+    },
+    InvalidSyntax {
+        position: usize,
+        reason: String,
+    },
+    InvalidJson {
+        expected: ValueType,
+        reason: String,
+    },
+    InvalidTypeCode {
+        position: usize,
+        reason: String,
+    },
+    UnsupportedVersion {
+        version: u8,
+    },
+    NotByteAligned {
+        bit_len: usize,
+    },
+    NodeCollision {
+        ids: Vec<usize>,
+    },
+    DivisionByZero,
+    /// There is no integer type of twice the width of the given type.
+    NoWiderType {
+        ty: ValueType,
+    },
+    ArrayElement {
+        index: usize,
+        source: Box<TypeError>,
+    },
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeError::InvalidLength {
+                expected,
+                actual,
+                path,
+            } => {
+                write!(
+                    f,
+                    "Invalid binary representation length: expected: {expected}, actual: {actual}"
+                )?;
+                if !path.is_empty() {
+                    write!(f, ", at path {path:?}")?;
+                }
+                Ok(())
+            }
+            TypeError::UnexpectedType { expected, actual } => {
+                write!(f, "Unexpected type, expected: {expected}, actual: {actual}")
+            }
+            TypeError::ZeroLengthArray => write!(f, "Zero-length arrays are not supported"),
+            TypeError::NotInteger { actual } => {
+                write!(f, "Expected an integer type, actual: {actual}")
+            }
+            TypeError::InvalidRepr {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Binary representation does not match type at path {path:?}, expected: {expected}, actual: {actual}"
+            ),
+            TypeError::InvalidSyntax { position, reason } => {
+                write!(f, "Failed to parse value at position {position}: {reason}")
+            }
+            TypeError::InvalidJson { expected, reason } => {
+                write!(f, "Invalid JSON for type {expected}: {reason}")
+            }
+            TypeError::InvalidTypeCode { position, reason } => {
+                write!(f, "Invalid type code at byte {position}: {reason}")
+            }
+            TypeError::UnsupportedVersion { version } => write!(
+                f,
+                "Unsupported value encoding version: expected: {VALUE_ENCODING_VERSION}, actual: {version}"
+            ),
+            TypeError::NotByteAligned { bit_len } => {
+                write!(f, "Value is not byte aligned, bit length: {bit_len}")
+            }
+            TypeError::NodeCollision { ids } => {
+                write!(f, "Binary representations share nodes: {ids:?}")
+            }
+            TypeError::DivisionByZero => write!(f, "Division by zero"),
+            TypeError::NoWiderType { ty } => {
+                write!(f, "No integer type of twice the width of {ty} exists")
+            }
+            TypeError::ArrayElement { index, source } => {
+                write!(f, "Invalid array element at index {index}: {source}")
+            }
+        }
+    }
+}
+
+impl TypeError {
+    /// Creates an `InvalidLength` error with an empty path.
+    pub(crate) fn invalid_length(expected: usize, actual: usize) -> Self {
+        TypeError::InvalidLength {
+            expected,
+            actual,
+            path: Vec::new(),
+        }
+    }
+
+    /// Prepends the index of the element the error occurred in to the path of an
+    /// `InvalidLength` error.
+    fn in_element(mut self, index: usize) -> Self {
+        if let TypeError::InvalidLength { path, .. } = &mut self {
+            path.insert(0, index);
+        }
+        self
+    }
+}
+
+impl core::error::Error for TypeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            TypeError::ArrayElement { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
 }
 
 /// A type that can be represented in binary form.
@@ -41,15 +186,87 @@ pub trait StaticValueType: Into<Value> {
     fn value_type() -> ValueType;
 }
 
+impl<T: BinaryLength, const N: usize> BinaryLength for [T; N] {
+    const LEN: usize = T::LEN * N;
+}
+
+impl<T: StaticValueType, const N: usize> StaticValueType for [T; N] {
+    fn value_type() -> ValueType {
+        ValueType::Array(Box::new(T::value_type()), N)
+    }
+}
+
+impl<T: ToBinaryRepr + BinaryLength, const N: usize> ToBinaryRepr for [T; N] {
+    type Repr = [T::Repr; N];
+
+    fn len(&self) -> usize {
+        self.iter().map(|v| v.len()).sum()
+    }
+
+    fn new_bin_repr(nodes: &[Node<Feed>]) -> Result<Self::Repr, TypeError> {
+        if nodes.len() != <Self as BinaryLength>::LEN {
+            return Err(TypeError::invalid_length(
+                <Self as BinaryLength>::LEN,
+                nodes.len(),
+            ));
+        }
+
+        let mut reprs = Vec::with_capacity(N);
+        for i in 0..N {
+            let nodes = &nodes[i * T::LEN..(i + 1) * T::LEN];
+            reprs.push(T::new_bin_repr(nodes).map_err(|e| e.in_element(i))?);
+        }
+
+        let mut reprs = reprs.into_iter();
+        Ok(core::array::from_fn(|_| {
+            reprs.next().expect("array has N elements")
+        }))
+    }
+}
+
+/// A visitor over the structure of a [`ValueType`], see [`ValueType::accept`].
+pub trait ValueTypeVisitor {
+    /// Visits a scalar type.
+    fn visit_scalar(&mut self, ty: &ValueType);
+
+    /// Called before visiting the elements of an array.
+    fn enter_array(&mut self, _elem: &ValueType, _len: usize) {}
+
+    /// Called after visiting the elements of an array.
+    fn exit_array(&mut self, _elem: &ValueType, _len: usize) {}
+
+    /// Called before visiting the fields of a struct.
+    fn enter_struct(&mut self, _fields: &[ValueType]) {}
+
+    /// Called after visiting the fields of a struct.
+    fn exit_struct(&mut self, _fields: &[ValueType]) {}
+}
+
 /// A type that has a constant bit length.
 pub trait BinaryLength {
     /// The length of the type in bits.
     const LEN: usize;
 }
 
+/// The minimum number of elements of an array for [`BinaryRepr::from_bin_repr`] to
+/// decode them in parallel.
+#[cfg(feature = "rayon")]
+pub const PAR_DECODE_MIN_ELEMENTS: usize = 1024;
+
 /// A binary representation of a type.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(
+        check_bytes,
+        bound(serialize = "__S: rkyv::ser::ScratchSpace + rkyv::ser::Serializer")
+    ),
+    archive_attr(check_bytes(
+        bound = "__C: rkyv::validation::ArchiveContext, <__C as rkyv::Fallible>::Error: rkyv::bytecheck::Error"
+    ))
+)]
 #[non_exhaustive]
 #[allow(clippy::large_enum_variant, missing_docs)]
 pub enum BinaryRepr {
@@ -59,10 +276,26 @@ pub enum BinaryRepr {
     U32(U32),
     U64(U64),
     U128(U128),
-    Array(Vec<BinaryRepr>),
+    Uint(Uint),
+    Array(#[cfg_attr(feature = "rkyv", omit_bounds, archive_attr(omit_bounds))] Vec<BinaryRepr>),
+    Struct(#[cfg_attr(feature = "rkyv", omit_bounds, archive_attr(omit_bounds))] Vec<BinaryRepr>),
 }
 
 impl BinaryRepr {
+    /// Creates a binary representation of the given type from a flat slice of nodes.
+    ///
+    /// Types with zero-width parts, such as arrays of empty structs, are supported. Returns
+    /// an error if the number of nodes does not match the length of the type, or if the
+    /// type contains a zero-length array.
+    ///
+    /// # Arguments
+    ///
+    /// * `ty` - The type of the value.
+    /// * `nodes` - The nodes of the value, in lsb0 order.
+    pub fn from_nodes(ty: &ValueType, nodes: &[Node<Feed>]) -> Result<BinaryRepr, TypeError> {
+        ty.to_bin_repr(nodes)
+    }
+
     /// Returns the type of the value.
     pub fn value_type(&self) -> ValueType {
         match self {
@@ -72,7 +305,35 @@ impl BinaryRepr {
             BinaryRepr::U32(_) => ValueType::U32,
             BinaryRepr::U64(_) => ValueType::U64,
             BinaryRepr::U128(_) => ValueType::U128,
+            BinaryRepr::Uint(v) => ValueType::Uint(v.0.len()),
             BinaryRepr::Array(v) => ValueType::Array(Box::new(v[0].value_type()), v.len()),
+            BinaryRepr::Struct(v) => ValueType::Struct(v.iter().map(|v| v.value_type()).collect()),
+        }
+    }
+
+    /// Returns the type of the value, without any of its nodes.
+    ///
+    /// This is derived from the structure of the representation alone. Unlike
+    /// [`BinaryRepr::value_type`], it never panics: the element type of an empty array is
+    /// unknown, so it is reported as an empty struct.
+    ///
+    /// The node ids of a representation are only meaningful within the circuit it belongs to.
+    /// When only the shape of a value is needed, e.g. in a manifest, serialize the returned
+    /// [`ValueType`] instead of the representation itself.
+    pub fn to_value_type(&self) -> ValueType {
+        match self {
+            BinaryRepr::Array(v) => ValueType::Array(
+                Box::new(
+                    v.first()
+                        .map(|v| v.to_value_type())
+                        .unwrap_or(ValueType::Struct(Vec::new())),
+                ),
+                v.len(),
+            ),
+            BinaryRepr::Struct(v) => {
+                ValueType::Struct(v.iter().map(|v| v.to_value_type()).collect())
+            }
+            repr => repr.value_type(),
         }
     }
 
@@ -86,7 +347,8 @@ impl BinaryRepr {
             BinaryRepr::U32(U32 { .. }) => 32,
             BinaryRepr::U64(U64 { .. }) => 64,
             BinaryRepr::U128(U128 { .. }) => 128,
-            BinaryRepr::Array(v) => v.iter().map(|v| v.len()).sum(),
+            BinaryRepr::Uint(v) => v.0.len(),
+            BinaryRepr::Array(v) | BinaryRepr::Struct(v) => v.iter().map(|v| v.len()).sum(),
         }
     }
 
@@ -99,8 +361,49 @@ impl BinaryRepr {
             BinaryRepr::U32(v) => Box::new(v.0.iter()),
             BinaryRepr::U64(v) => Box::new(v.0.iter()),
             BinaryRepr::U128(v) => Box::new(v.0.iter()),
-            BinaryRepr::Array(v) => Box::new(v.iter().flat_map(|v| v.iter())),
+            BinaryRepr::Uint(v) => Box::new(v.0.iter()),
+            BinaryRepr::Array(v) | BinaryRepr::Struct(v) => {
+                Box::new(v.iter().flat_map(|v| v.iter()))
+            }
+        }
+    }
+
+    /// Returns an iterator over the nodes of the representation in chunks of 8.
+    ///
+    /// The nodes are grouped in the same order as [`BinaryRepr::iter`]. Returns an error
+    /// if the length of the representation is not a multiple of 8.
+    pub fn try_byte_chunks(&self) -> Result<impl Iterator<Item = [Node<Feed>; 8]> + '_, TypeError> {
+        let len = self.len();
+        if !len.is_multiple_of(8) {
+            return Err(TypeError::invalid_length(len.next_multiple_of(8), len));
         }
+
+        let mut nodes = self.iter().copied();
+        Ok(core::iter::from_fn(move || {
+            let first = nodes.next()?;
+            Some(core::array::from_fn(|i| {
+                if i == 0 {
+                    first
+                } else {
+                    nodes.next().expect("length should be a multiple of 8")
+                }
+            }))
+        }))
+    }
+
+    /// Returns the minimum and maximum node IDs contained in the representation.
+    ///
+    /// Returns `None` if the representation contains no nodes.
+    pub fn node_range(&self) -> Option<(usize, usize)> {
+        self.iter().fold(None, |range, node| match range {
+            None => Some((node.id, node.id)),
+            Some((min, max)) => Some((min.min(node.id), max.max(node.id))),
+        })
+    }
+
+    /// Returns whether the representation contains a node with the given ID.
+    pub fn contains_node(&self, id: usize) -> bool {
+        self.iter().any(|node| node.id == id)
     }
 
     /// Returns a mutable iterator over the nodes.
@@ -112,7 +415,78 @@ impl BinaryRepr {
             BinaryRepr::U32(v) => Box::new(v.0.iter_mut()),
             BinaryRepr::U64(v) => Box::new(v.0.iter_mut()),
             BinaryRepr::U128(v) => Box::new(v.0.iter_mut()),
-            BinaryRepr::Array(v) => Box::new(v.iter_mut().flat_map(|v| v.iter_mut())),
+            BinaryRepr::Uint(v) => Box::new(v.0.iter_mut()),
+            BinaryRepr::Array(v) | BinaryRepr::Struct(v) => {
+                Box::new(v.iter_mut().flat_map(|v| v.iter_mut()))
+            }
+        }
+    }
+
+    /// Returns a copy of the representation with the order of the nodes within each
+    /// scalar reversed, eg. to adapt to external formats which are msb0.
+    ///
+    /// The node IDs are preserved, only their order changes. Struct fields are kept in
+    /// order.
+    ///
+    /// # Arguments
+    ///
+    /// * `reverse_elements` - Whether to also reverse the order of the elements of arrays.
+    pub fn reversed(&self, reverse_elements: bool) -> BinaryRepr {
+        let mut repr = self.clone();
+        repr.reverse(reverse_elements);
+        repr
+    }
+
+    /// Checks that no two of the given representations reference the same node.
+    ///
+    /// A node may appear more than once within a single representation. Returns an error
+    /// listing the colliding node IDs in ascending order.
+    ///
+    /// # Arguments
+    ///
+    /// * `reprs` - The representations to check.
+    pub fn disjoint_nodes(reprs: &[&BinaryRepr]) -> Result<(), TypeError> {
+        let mut ids: Vec<(usize, usize)> = Vec::new();
+        for (idx, repr) in reprs.iter().enumerate() {
+            let mut repr_ids: Vec<usize> = repr.iter().map(|node| node.id).collect();
+            repr_ids.sort_unstable();
+            repr_ids.dedup();
+            ids.extend(repr_ids.into_iter().map(|id| (id, idx)));
+        }
+        ids.sort_unstable();
+
+        // Each representation's IDs are deduplicated, so adjacent equal IDs always come
+        // from different representations.
+        let mut collisions: Vec<usize> = ids
+            .windows(2)
+            .filter(|w| w[0].0 == w[1].0)
+            .map(|w| w[0].0)
+            .collect();
+        collisions.dedup();
+
+        if collisions.is_empty() {
+            Ok(())
+        } else {
+            Err(TypeError::NodeCollision { ids: collisions })
+        }
+    }
+
+    fn reverse(&mut self, reverse_elements: bool) {
+        match self {
+            BinaryRepr::Bit(_) => {}
+            BinaryRepr::U8(v) => v.0.reverse(),
+            BinaryRepr::U16(v) => v.0.reverse(),
+            BinaryRepr::U32(v) => v.0.reverse(),
+            BinaryRepr::U64(v) => v.0.reverse(),
+            BinaryRepr::U128(v) => v.0.reverse(),
+            BinaryRepr::Uint(v) => v.0.reverse(),
+            BinaryRepr::Array(v) => {
+                if reverse_elements {
+                    v.reverse();
+                }
+                v.iter_mut().for_each(|v| v.reverse(reverse_elements))
+            }
+            BinaryRepr::Struct(v) => v.iter_mut().for_each(|v| v.reverse(reverse_elements)),
         }
     }
 
@@ -125,12 +499,18 @@ impl BinaryRepr {
             BinaryRepr::U32(v) => v.shift_left(offset),
             BinaryRepr::U64(v) => v.shift_left(offset),
             BinaryRepr::U128(v) => v.shift_left(offset),
-            BinaryRepr::Array(v) => v.iter_mut().for_each(|v| v.shift_left(offset)),
+            BinaryRepr::Uint(v) => v.shift_left(offset),
+            BinaryRepr::Array(v) | BinaryRepr::Struct(v) => {
+                v.iter_mut().for_each(|v| v.shift_left(offset))
+            }
         }
     }
 
     /// Decodes the type from a binary value.
     ///
+    /// With the `rayon` feature, the elements of a top-level array with at least
+    /// `PAR_DECODE_MIN_ELEMENTS` elements are decoded in parallel.
+    ///
     /// # Arguments
     ///
     /// * `bits` - The bit representation of the type.
@@ -139,11 +519,20 @@ impl BinaryRepr {
     ///
     /// The decoded value.
     pub fn from_bin_repr(&self, bits: &[bool]) -> Result<Value, TypeError> {
+        #[cfg(feature = "rayon")]
+        if let BinaryRepr::Array(v) = self {
+            if v.len() >= PAR_DECODE_MIN_ELEMENTS && bits.len() == self.len() {
+                return Ok(Value::Array(BinaryRepr::decode_elements_par(v, bits)?));
+            }
+        }
+
+        self.decode(bits)
+    }
+
+    /// Decodes the type from a binary value on the current thread.
+    fn decode(&self, bits: &[bool]) -> Result<Value, TypeError> {
         if bits.len() != self.len() {
-            return Err(TypeError::InvalidLength {
-                expected: self.len(),
-                actual: bits.len(),
-            });
+            return Err(TypeError::invalid_length(self.len(), bits.len()));
         }
         match self {
             BinaryRepr::Bit(_) => Ok(Value::Bit(bits[0])),
@@ -152,12 +541,181 @@ impl BinaryRepr {
             BinaryRepr::U32(_) => Ok(Value::U32(u32::from_lsb0_iter(bits.iter().copied()))),
             BinaryRepr::U64(_) => Ok(Value::U64(u64::from_lsb0_iter(bits.iter().copied()))),
             BinaryRepr::U128(_) => Ok(Value::U128(u128::from_lsb0_iter(bits.iter().copied()))),
-            BinaryRepr::Array(v) => Ok(Value::Array(
-                v.iter()
-                    .zip(bits.chunks(v[0].len()))
-                    .map(|(v, bits)| v.from_bin_repr(bits).unwrap())
-                    .collect(),
-            )),
+            BinaryRepr::Uint(_) => Ok(Value::Uint(bits.to_vec())),
+            BinaryRepr::Array(v) if v.is_empty() => Err(TypeError::ZeroLengthArray),
+            BinaryRepr::Array(v) => Ok(Value::Array(BinaryRepr::decode_elements(v, bits)?)),
+            BinaryRepr::Struct(v) => {
+                let mut offset = 0;
+                Ok(Value::Struct(
+                    v.iter()
+                        .enumerate()
+                        .map(|(i, v)| {
+                            let bits = &bits[offset..offset + v.len()];
+                            offset += v.len();
+                            v.decode(bits).map_err(|e| e.in_element(i))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                ))
+            }
+        }
+    }
+
+    /// Decodes as much of the value as the available bits allow.
+    ///
+    /// This is intended for diagnosing truncated inputs. If the number of bits does not
+    /// match the length of the representation, the returned value contains only the
+    /// elements and fields which could be fully decoded, along with a partially decoded
+    /// element if it is itself an array or struct. No value is returned if not even the
+    /// first scalar can be decoded, or if the representation cannot be decoded at all,
+    /// such as an array whose elements differ in length.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits` - The bits of the value.
+    ///
+    /// # Returns
+    ///
+    /// The decoded value, and an error describing why it is incomplete if it is.
+    pub fn from_bin_repr_partial(&self, bits: &[bool]) -> (Option<Value>, Option<TypeError>) {
+        if bits.len() == self.len() {
+            return match self.from_bin_repr(bits) {
+                Ok(value) => (Some(value), None),
+                Err(err) => (None, Some(err)),
+            };
+        }
+
+        let value = self.decode_prefix(&bits[..bits.len().min(self.len())]);
+
+        (
+            value,
+            Some(TypeError::invalid_length(self.len(), bits.len())),
+        )
+    }
+
+    /// Decodes the complete scalars of the value from a prefix of its bits, returning
+    /// `None` if not even the first scalar is complete.
+    fn decode_prefix(&self, bits: &[bool]) -> Option<Value> {
+        if bits.len() >= self.len() {
+            return self.decode(&bits[..self.len()]).ok();
+        }
+
+        let elems = match self {
+            BinaryRepr::Array(v) | BinaryRepr::Struct(v) => v,
+            _ => return None,
+        };
+
+        let mut values = Vec::new();
+        let mut offset = 0;
+        for elem in elems {
+            let Some(value) = elem.decode_prefix(&bits[offset.min(bits.len())..]) else {
+                break;
+            };
+            values.push(value);
+            offset += elem.len();
+        }
+
+        if values.is_empty() {
+            return None;
+        }
+
+        Some(match self {
+            BinaryRepr::Array(_) => Value::Array(values),
+            _ => Value::Struct(values),
+        })
+    }
+
+    /// Returns the bits of the element at `index` of an array whose elements are `width`
+    /// bits wide, clamped to the available bits.
+    ///
+    /// Unlike [`slice::chunks`], this also handles elements of zero width.
+    fn element_bits(bits: &[bool], width: usize, index: usize) -> &[bool] {
+        let start = index.saturating_mul(width).min(bits.len());
+        &bits[start..start.saturating_add(width).min(bits.len())]
+    }
+
+    /// Decodes the elements of an array, each from an equal length chunk of the bits.
+    fn decode_elements(elems: &[BinaryRepr], bits: &[bool]) -> Result<Vec<Value>, TypeError> {
+        let width = elems.first().map_or(0, BinaryRepr::len);
+        elems
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                #[cfg(feature = "tracing")]
+                let _span = tracing::trace_span!("decode_element", index = i).entered();
+
+                v.decode(BinaryRepr::element_bits(bits, width, i))
+                    .map_err(|e| e.in_element(i))
+            })
+            .collect()
+    }
+
+    /// Decodes the elements of an array in parallel, see [`BinaryRepr::decode_elements`].
+    ///
+    /// The elements themselves are decoded sequentially, so only one level of nesting
+    /// is parallelized.
+    #[cfg(feature = "rayon")]
+    fn decode_elements_par(elems: &[BinaryRepr], bits: &[bool]) -> Result<Vec<Value>, TypeError> {
+        use rayon::prelude::*;
+
+        // Spans entered on worker threads are parented explicitly, as the current span is
+        // not inherited from the calling thread.
+        #[cfg(feature = "tracing")]
+        let parent = tracing::Span::current();
+
+        let width = elems.first().map_or(0, BinaryRepr::len);
+        elems
+            .par_iter()
+            .enumerate()
+            .map(|(i, v)| {
+                #[cfg(feature = "tracing")]
+                let _span =
+                    tracing::trace_span!(parent: &parent, "decode_element", index = i).entered();
+
+                v.decode(BinaryRepr::element_bits(bits, width, i))
+                    .map_err(|e| e.in_element(i))
+            })
+            .collect()
+    }
+
+    /// Decodes a value from its binary representation, reinterpreting it as the given type.
+    ///
+    /// If the representation is an array of `U8`, it is reassembled into the requested
+    /// integer type with the bytes in the given order, see [`Value::from_byte_array`].
+    /// Otherwise, the requested type must be the type of the representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits` - The bits of the value.
+    /// * `interpret` - The type to interpret the value as.
+    /// * `endian` - The byte order of the bytes in the representation.
+    pub fn from_bin_repr_as(
+        &self,
+        bits: &[bool],
+        interpret: ValueType,
+        endian: Endian,
+    ) -> Result<Value, TypeError> {
+        if interpret.len() != self.len() {
+            return Err(TypeError::invalid_length(self.len(), interpret.len()));
+        }
+
+        let value = self.from_bin_repr(bits)?;
+        let ty = self.value_type();
+        if ty == interpret {
+            return Ok(value);
+        }
+
+        match ty {
+            ValueType::Array(elem, _) if *elem == ValueType::U8 => match (endian, value) {
+                (Endian::Little, Value::Array(mut bytes)) => {
+                    bytes.reverse();
+                    Value::from_byte_array(&Value::Array(bytes), &interpret)
+                }
+                (_, value) => Value::from_byte_array(&value, &interpret),
+            },
+            ty => Err(TypeError::UnexpectedType {
+                expected: ty,
+                actual: interpret,
+            }),
         }
     }
 }
@@ -171,8 +729,68 @@ impl Display for BinaryRepr {
             BinaryRepr::U32(v) => write!(f, "U32({:?})", v.0),
             BinaryRepr::U64(v) => write!(f, "U64({:?})", v.0),
             BinaryRepr::U128(v) => write!(f, "U128({:?})", v.0),
+            BinaryRepr::Uint(v) => write!(f, "Uint({:?})", v.0),
             BinaryRepr::Array(v) => write!(f, "Array({:?})", v),
+            BinaryRepr::Struct(v) => write!(f, "Struct({:?})", v),
+        }
+    }
+}
+
+/// A binary representation paired with the type it was validated against.
+///
+/// Functions which accept a `TypedRepr` can rely on the representation matching its type.
+#[derive(Debug, Clone)]
+pub struct TypedRepr {
+    repr: BinaryRepr,
+    ty: ValueType,
+}
+
+impl TypedRepr {
+    /// Creates a new typed representation, checking that the representation matches
+    /// the type.
+    ///
+    /// # Arguments
+    ///
+    /// * `repr` - The binary representation.
+    /// * `ty` - The type of the representation.
+    pub fn new(repr: BinaryRepr, ty: ValueType) -> Result<Self, TypeError> {
+        ty.validate_repr(&repr)?;
+
+        Ok(Self { repr, ty })
+    }
+
+    /// Returns the binary representation.
+    pub fn repr(&self) -> &BinaryRepr {
+        &self.repr
+    }
+
+    /// Returns the type of the representation.
+    pub fn ty(&self) -> &ValueType {
+        &self.ty
+    }
+
+    /// Returns the binary representation and its type.
+    pub fn into_parts(self) -> (BinaryRepr, ValueType) {
+        (self.repr, self.ty)
+    }
+
+    /// Decodes a value of the attached type from its bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits` - The bits of the value.
+    pub fn decode(&self, bits: &[bool]) -> Result<Value, TypeError> {
+        if bits.len() != self.ty.len() {
+            return Err(TypeError::invalid_length(self.ty.len(), bits.len()));
         }
+
+        Ok(Value::decode_lsb0(&self.ty, bits))
+    }
+}
+
+impl From<TypedRepr> for BinaryRepr {
+    fn from(typed: TypedRepr) -> Self {
+        typed.repr
     }
 }
 
@@ -180,6 +798,11 @@ macro_rules! define_binary_value {
     ($ty:ty, $id:ident, $len:expr) => {
         #[derive(Debug, Clone, Copy)]
         #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(
+            feature = "rkyv",
+            derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+            archive(check_bytes)
+        )]
         #[allow(missing_docs)]
         pub struct $id(
             #[cfg_attr(feature = "serde", serde(with = "serde_arrays"))] [Node<Feed>; $len],
@@ -207,11 +830,9 @@ macro_rules! define_binary_value {
             }
 
             fn new_bin_repr(nodes: &[Node<Feed>]) -> Result<$id, TypeError> {
-                let nodes: [Node<Feed>; $len] =
-                    nodes.try_into().map_err(|_| TypeError::InvalidLength {
-                        expected: $len,
-                        actual: nodes.len(),
-                    })?;
+                let nodes: [Node<Feed>; $len] = nodes
+                    .try_into()
+                    .map_err(|_| TypeError::invalid_length($len, nodes.len()))?;
                 Ok($id::new(nodes))
             }
         }
@@ -220,31 +841,6 @@ macro_rules! define_binary_value {
             const LEN: usize = $len;
         }
 
-        impl<const N: usize> BinaryLength for [$ty; N] {
-            const LEN: usize = $len * N;
-        }
-
-        impl<const N: usize> ToBinaryRepr for [$ty; N] {
-            type Repr = [$id; N];
-
-            fn len(&self) -> usize {
-                $len * N
-            }
-
-            fn new_bin_repr(nodes: &[Node<Feed>]) -> Result<[$id; N], TypeError> {
-                if nodes.len() != $len * N {
-                    return Err(TypeError::InvalidLength {
-                        expected: $len * N,
-                        actual: nodes.len(),
-                    });
-                }
-
-                Ok(std::array::from_fn(|i| {
-                    $id(nodes[i * $len..(i + 1) * $len].try_into().unwrap())
-                }))
-            }
-        }
-
         impl ToBinaryRepr for Vec<$ty> {
             type Repr = Vec<$id>;
 
@@ -257,10 +853,7 @@ macro_rules! define_binary_value {
                 // nearest rounded up length
                 let expected_len = nodes.len() / $len + (nodes.len() % $len != 0) as usize;
                 if nodes.len() != expected_len {
-                    return Err(TypeError::InvalidLength {
-                        expected: expected_len,
-                        actual: nodes.len(),
-                    });
+                    return Err(TypeError::invalid_length(expected_len, nodes.len()));
                 }
 
                 let values: Vec<$id> = nodes
@@ -298,9 +891,9 @@ macro_rules! define_binary_value {
             }
         }
 
-        impl<const N: usize> From<[$ty; N]> for Value {
-            fn from(v: [$ty; N]) -> Self {
-                Self::Array(v.into_iter().map(|v| v.into()).collect())
+        impl From<&$ty> for Value {
+            fn from(v: &$ty) -> Self {
+                Self::$id(*v)
             }
         }
 
@@ -344,7 +937,10 @@ macro_rules! define_binary_value {
                     Value::Array(v) => {
                         let mut values = [<$ty>::default(); N];
                         for (i, v) in v.into_iter().enumerate() {
-                            values[i] = v.try_into()?;
+                            values[i] = v.try_into().map_err(|e| TypeError::ArrayElement {
+                                index: i,
+                                source: Box::new(e),
+                            })?;
                         }
                         Ok(values)
                     }
@@ -360,15 +956,27 @@ macro_rules! define_binary_value {
             type Error = TypeError;
 
             fn try_from(value: Value) -> Result<Self, Self::Error> {
+                // Vectors may have any length, which is denoted by a length of 0.
+                let expected = ValueType::Array(Box::new(ValueType::$id), 0);
                 match value {
-                    Value::Array(v) => Ok(v
-                        .into_iter()
-                        .map(|v| v.try_into())
-                        .collect::<Result<Vec<_>, _>>()?),
-                    v => Err(TypeError::UnexpectedType {
-                        expected: ValueType::Array(Box::new(ValueType::$id), 0),
-                        actual: v.value_type(),
-                    }),
+                    Value::Array(v) if v.is_empty() => Ok(Vec::new()),
+                    value => {
+                        let actual = value.value_type();
+                        if !expected.matches(&actual) {
+                            return Err(TypeError::UnexpectedType { expected, actual });
+                        }
+
+                        value
+                            .into_iter()
+                            .enumerate()
+                            .map(|(i, v)| {
+                                v.try_into().map_err(|e| TypeError::ArrayElement {
+                                    index: i,
+                                    source: Box::new(e),
+                                })
+                            })
+                            .collect()
+                    }
                 }
             }
         }
@@ -379,12 +987,6 @@ macro_rules! define_binary_value {
             }
         }
 
-        impl<const N: usize> From<[$id; N]> for BinaryRepr {
-            fn from(v: [$id; N]) -> Self {
-                BinaryRepr::Array(v.into_iter().map(|v| v.into()).collect())
-            }
-        }
-
         impl From<&[$id]> for BinaryRepr {
             fn from(v: &[$id]) -> Self {
                 BinaryRepr::Array(v.iter().map(|v| (*v).into()).collect())
@@ -410,6 +1012,28 @@ macro_rules! define_binary_value {
                 }
             }
         }
+
+        impl TryFrom<&BinaryRepr> for $id {
+            type Error = TypeError;
+
+            fn try_from(value: &BinaryRepr) -> Result<Self, Self::Error> {
+                <&$id>::try_from(value).copied()
+            }
+        }
+
+        impl<'a> TryFrom<&'a BinaryRepr> for &'a $id {
+            type Error = TypeError;
+
+            fn try_from(value: &'a BinaryRepr) -> Result<Self, Self::Error> {
+                match value {
+                    BinaryRepr::$id(v) => Ok(v),
+                    v => Err(TypeError::UnexpectedType {
+                        expected: ValueType::$id,
+                        actual: v.value_type(),
+                    }),
+                }
+            }
+        }
     };
 }
 
@@ -420,20 +1044,139 @@ define_binary_value!(u32, U32, 32);
 define_binary_value!(u64, U64, 64);
 define_binary_value!(u128, U128, 128);
 
-/// A value type that can be encoded into a binary representation.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[non_exhaustive]
-#[allow(missing_docs)]
-pub enum ValueType {
-    Bit,
-    U8,
-    U16,
+/// The binary representation of an unsigned integer of arbitrary width.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(check_bytes)
+)]
+pub struct Uint(Vec<Node<Feed>>);
+
+impl Uint {
+    pub(crate) fn new(nodes: Vec<Node<Feed>>) -> Self {
+        Uint(nodes)
+    }
+
+    pub(crate) fn shift_left(&mut self, offset: usize) {
+        self.0.iter_mut().for_each(|v| v.shift_left(offset))
+    }
+
+    /// Returns the width of the integer in bits.
+    pub fn width(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl AsRef<[Node<Feed>]> for Uint {
+    fn as_ref(&self) -> &[Node<Feed>] {
+        &self.0
+    }
+}
+
+impl<T: Into<BinaryRepr>, const N: usize> From<[T; N]> for BinaryRepr {
+    fn from(v: [T; N]) -> Self {
+        BinaryRepr::Array(v.into_iter().map(|v| v.into()).collect())
+    }
+}
+
+impl From<Uint> for BinaryRepr {
+    fn from(v: Uint) -> Self {
+        BinaryRepr::Uint(v)
+    }
+}
+
+impl TryFrom<BinaryRepr> for Uint {
+    type Error = TypeError;
+
+    fn try_from(value: BinaryRepr) -> Result<Self, Self::Error> {
+        match value {
+            BinaryRepr::Uint(v) => Ok(v),
+            v => Err(TypeError::UnexpectedType {
+                expected: ValueType::Uint(v.len()),
+                actual: v.value_type(),
+            }),
+        }
+    }
+}
+
+macro_rules! impl_from_nonzero {
+    ($nz:ty, $id:ident) => {
+        impl From<$nz> for Value {
+            fn from(v: $nz) -> Self {
+                Self::$id(v.get())
+            }
+        }
+    };
+}
+
+impl<T: Into<Value>, const N: usize> From<[T; N]> for Value {
+    fn from(v: [T; N]) -> Self {
+        Self::Array(v.into_iter().map(|v| v.into()).collect())
+    }
+}
+
+impl_from_nonzero!(core::num::NonZeroU8, U8);
+impl_from_nonzero!(core::num::NonZeroU16, U16);
+impl_from_nonzero!(core::num::NonZeroU32, U32);
+impl_from_nonzero!(core::num::NonZeroU64, U64);
+impl_from_nonzero!(core::num::NonZeroU128, U128);
+
+/// A value type that can be encoded into a binary representation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    archive(
+        check_bytes,
+        bound(serialize = "__S: rkyv::ser::ScratchSpace + rkyv::ser::Serializer")
+    ),
+    archive_attr(check_bytes(
+        bound = "__C: rkyv::validation::ArchiveContext, <__C as rkyv::Fallible>::Error: rkyv::bytecheck::Error"
+    ))
+)]
+#[non_exhaustive]
+#[allow(missing_docs)]
+pub enum ValueType {
+    Bit,
+    U8,
+    U16,
     U32,
     U64,
     U128,
-    Array(Box<ValueType>, usize),
+    /// An unsigned integer of arbitrary width in bits.
+    Uint(usize),
+    Array(
+        #[cfg_attr(feature = "rkyv", omit_bounds, archive_attr(omit_bounds))] Box<ValueType>,
+        usize,
+    ),
+    /// A structure of heterogeneous fields, laid out in order.
+    Struct(#[cfg_attr(feature = "rkyv", omit_bounds, archive_attr(omit_bounds))] Vec<ValueType>),
+    /// A type with a semantic label, laid out identically to its inner type.
+    Named {
+        name: String,
+        #[cfg_attr(feature = "rkyv", omit_bounds, archive_attr(omit_bounds))]
+        inner: Box<ValueType>,
+    },
 }
 
+const TYPE_CODE_BIT: u8 = 0x00;
+const TYPE_CODE_U8: u8 = 0x01;
+const TYPE_CODE_U16: u8 = 0x02;
+const TYPE_CODE_U32: u8 = 0x03;
+const TYPE_CODE_U64: u8 = 0x04;
+const TYPE_CODE_U128: u8 = 0x05;
+const TYPE_CODE_UINT: u8 = 0x06;
+const TYPE_CODE_ARRAY: u8 = 0x07;
+const TYPE_CODE_STRUCT: u8 = 0x08;
+const TYPE_CODE_NAMED: u8 = 0x09;
+
+/// The maximum nesting depth of arrays, structs and named types accepted when parsing a
+/// type code, which bounds the recursion on untrusted input.
+pub const MAX_TYPE_DEPTH: usize = 64;
+
 impl ValueType {
     /// Creates a new value type.
     pub fn new<T: StaticValueType>() -> Self {
@@ -441,10 +1184,63 @@ impl ValueType {
     }
 
     /// Creates a new array value type.
+    ///
+    /// Empty arrays are not supported, see [`ValueType::try_new_array`].
     pub fn new_array<T: StaticValueType>(len: usize) -> Self {
+        debug_assert!(len > 0, "zero-length arrays are not supported");
         ValueType::Array(Box::new(T::value_type()), len)
     }
 
+    /// Creates a new array value type, returning an error if the length is zero.
+    ///
+    /// Empty arrays are not supported because the type of an array value is
+    /// determined by its first element.
+    pub fn try_new_array<T: StaticValueType>(len: usize) -> Result<Self, TypeError> {
+        if len == 0 {
+            return Err(TypeError::ZeroLengthArray);
+        }
+
+        Ok(ValueType::Array(Box::new(T::value_type()), len))
+    }
+
+    /// Returns the `Bit` value type.
+    ///
+    /// Unlike [`ValueType::new`], the scalar constructors are `const`, so they can be used
+    /// to declare types in a static context.
+    pub const fn bit() -> Self {
+        ValueType::Bit
+    }
+
+    /// Returns the `U8` value type.
+    pub const fn u8() -> Self {
+        ValueType::U8
+    }
+
+    /// Returns the `U16` value type.
+    pub const fn u16() -> Self {
+        ValueType::U16
+    }
+
+    /// Returns the `U32` value type.
+    pub const fn u32() -> Self {
+        ValueType::U32
+    }
+
+    /// Returns the `U64` value type.
+    pub const fn u64() -> Self {
+        ValueType::U64
+    }
+
+    /// Returns the `U128` value type.
+    pub const fn u128() -> Self {
+        ValueType::U128
+    }
+
+    /// Returns the `Uint` value type with the given width in bits.
+    pub const fn uint(width: usize) -> Self {
+        ValueType::Uint(width)
+    }
+
     /// Returns the length of the value type in bits.
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
@@ -455,21 +1251,354 @@ impl ValueType {
             ValueType::U32 => 32,
             ValueType::U64 => 64,
             ValueType::U128 => 128,
+            ValueType::Uint(width) => *width,
             ValueType::Array(ty, len) => ty.len() * len,
+            ValueType::Struct(fields) => fields.iter().map(|ty| ty.len()).sum(),
+            ValueType::Named { inner, .. } => inner.len(),
         }
     }
 
     /// Returns whether the value type is an array.
     pub fn is_array(&self) -> bool {
-        matches!(self, ValueType::Array(..))
+        matches!(self.unnamed(), ValueType::Array(..))
+    }
+
+    /// Returns the type with any [`ValueType::Named`] labels on the outside removed.
+    pub fn unnamed(&self) -> &ValueType {
+        match self {
+            ValueType::Named { inner, .. } => inner.unnamed(),
+            ty => ty,
+        }
+    }
+
+    /// Returns whether the value type matches another, treating an array length of 0 in
+    /// either type as a wildcard which matches arrays of any length.
+    ///
+    /// Unlike `==`, this is useful for comparing against types of variable length
+    /// collections such as `Vec<u8>`. Names are ignored.
+    pub fn matches(&self, other: &ValueType) -> bool {
+        match (self.unnamed(), other.unnamed()) {
+            (ValueType::Array(a, a_len), ValueType::Array(b, b_len)) => {
+                (*a_len == 0 || *b_len == 0 || a_len == b_len) && a.matches(b)
+            }
+            (ValueType::Struct(a), ValueType::Struct(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.matches(b))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Traverses the structure of the type with the given visitor.
+    ///
+    /// Every element of an array is visited, and scalars are visited in the same order
+    /// as their bits appear in the binary representation of the type.
+    ///
+    /// # Arguments
+    ///
+    /// * `visitor` - The visitor.
+    pub fn accept(&self, visitor: &mut impl ValueTypeVisitor) {
+        match self {
+            ValueType::Array(elem, len) => {
+                visitor.enter_array(elem, *len);
+                for _ in 0..*len {
+                    elem.accept(visitor);
+                }
+                visitor.exit_array(elem, *len);
+            }
+            ValueType::Struct(fields) => {
+                visitor.enter_struct(fields);
+                for field in fields {
+                    field.accept(visitor);
+                }
+                visitor.exit_struct(fields);
+            }
+            ValueType::Named { inner, .. } => inner.accept(visitor),
+            ty => visitor.visit_scalar(ty),
+        }
+    }
+
+    /// Returns whether the value type is an unsigned integer type, ie. one of `U8`, `U16`,
+    /// `U32`, `U64`, `U128` or `Uint`.
+    ///
+    /// Unlike [`ValueType::is_scalar`], names are not looked through, as a named
+    /// integer has to be unwrapped before it can be used as one.
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            ValueType::U8
+                | ValueType::U16
+                | ValueType::U32
+                | ValueType::U64
+                | ValueType::U128
+                | ValueType::Uint(_)
+        )
+    }
+
+    /// Returns whether the value type is a scalar, ie. neither an array nor a struct.
+    pub fn is_scalar(&self) -> bool {
+        !matches!(self.unnamed(), ValueType::Array(..) | ValueType::Struct(..))
+    }
+
+    /// Returns the width of the type in bits if it is a scalar, or the width of the
+    /// innermost element type if it is an array.
+    ///
+    /// Returns `None` for structs and arrays of structs.
+    pub fn scalar_bits(&self) -> Option<usize> {
+        match self {
+            ValueType::Array(ty, _) => ty.scalar_bits(),
+            ValueType::Struct(_) => None,
+            ValueType::Named { inner, .. } => inner.scalar_bits(),
+            ty => Some(ty.len()),
+        }
+    }
+
+    /// Returns a compact binary encoding of the type.
+    ///
+    /// Scalars are encoded as a single tag byte. Arbitrary width integers, arrays and
+    /// structs are encoded as a tag byte followed by a little-endian `u64` (the width,
+    /// length or field count respectively) and, for arrays and structs, the encodings
+    /// of the element or field types. Named types are encoded as a tag byte followed by
+    /// the length of the name as a little-endian `u64`, the UTF-8 bytes of the name and
+    /// the encoding of the inner type.
+    ///
+    /// The encoding can be decoded using [`ValueType::from_type_code`].
+    pub fn type_code(&self) -> Vec<u8> {
+        let mut code = Vec::new();
+        self.write_type_code(&mut code);
+        code
+    }
+
+    fn write_type_code(&self, code: &mut Vec<u8>) {
+        match self {
+            ValueType::Bit => code.push(TYPE_CODE_BIT),
+            ValueType::U8 => code.push(TYPE_CODE_U8),
+            ValueType::U16 => code.push(TYPE_CODE_U16),
+            ValueType::U32 => code.push(TYPE_CODE_U32),
+            ValueType::U64 => code.push(TYPE_CODE_U64),
+            ValueType::U128 => code.push(TYPE_CODE_U128),
+            ValueType::Uint(width) => {
+                code.push(TYPE_CODE_UINT);
+                code.extend_from_slice(&(*width as u64).to_le_bytes());
+            }
+            ValueType::Array(ty, len) => {
+                code.push(TYPE_CODE_ARRAY);
+                code.extend_from_slice(&(*len as u64).to_le_bytes());
+                ty.write_type_code(code);
+            }
+            ValueType::Struct(fields) => {
+                code.push(TYPE_CODE_STRUCT);
+                code.extend_from_slice(&(fields.len() as u64).to_le_bytes());
+                for field in fields {
+                    field.write_type_code(code);
+                }
+            }
+            ValueType::Named { name, inner } => {
+                code.push(TYPE_CODE_NAMED);
+                code.extend_from_slice(&(name.len() as u64).to_le_bytes());
+                code.extend_from_slice(name.as_bytes());
+                inner.write_type_code(code);
+            }
+        }
+    }
+
+    /// Parses a type from the encoding produced by [`ValueType::type_code`].
+    ///
+    /// Types nested more than [`MAX_TYPE_DEPTH`] levels deep are rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes to parse, which may contain trailing data.
+    ///
+    /// # Returns
+    ///
+    /// The type and the number of bytes consumed.
+    pub fn from_type_code(bytes: &[u8]) -> Result<(ValueType, usize), TypeError> {
+        let mut position = 0;
+        let ty = Self::read_type_code(bytes, &mut position, 0)?;
+        Ok((ty, position))
+    }
+
+    fn read_type_code(
+        bytes: &[u8],
+        position: &mut usize,
+        depth: usize,
+    ) -> Result<ValueType, TypeError> {
+        let Some(&tag) = bytes.get(*position) else {
+            return Err(TypeError::InvalidTypeCode {
+                position: *position,
+                reason: "unexpected end of input".to_string(),
+            });
+        };
+
+        if matches!(tag, TYPE_CODE_ARRAY | TYPE_CODE_STRUCT | TYPE_CODE_NAMED)
+            && depth >= MAX_TYPE_DEPTH
+        {
+            return Err(TypeError::InvalidTypeCode {
+                position: *position,
+                reason: format!("type is nested more than {MAX_TYPE_DEPTH} levels deep"),
+            });
+        }
+        *position += 1;
+
+        let ty = match tag {
+            TYPE_CODE_BIT => ValueType::Bit,
+            TYPE_CODE_U8 => ValueType::U8,
+            TYPE_CODE_U16 => ValueType::U16,
+            TYPE_CODE_U32 => ValueType::U32,
+            TYPE_CODE_U64 => ValueType::U64,
+            TYPE_CODE_U128 => ValueType::U128,
+            TYPE_CODE_UINT => ValueType::Uint(Self::read_type_code_len(bytes, position)?),
+            TYPE_CODE_ARRAY => {
+                let len = Self::read_type_code_len(bytes, position)?;
+                let ty = Self::read_type_code(bytes, position, depth + 1)?;
+                ValueType::Array(Box::new(ty), len)
+            }
+            TYPE_CODE_STRUCT => {
+                let count = Self::read_type_code_len(bytes, position)?;
+                let mut fields = Vec::new();
+                for _ in 0..count {
+                    fields.push(Self::read_type_code(bytes, position, depth + 1)?);
+                }
+                ValueType::Struct(fields)
+            }
+            TYPE_CODE_NAMED => {
+                let len = Self::read_type_code_len(bytes, position)?;
+                let name = position
+                    .checked_add(len)
+                    .and_then(|end| bytes.get(*position..end))
+                    .ok_or_else(|| TypeError::InvalidTypeCode {
+                        position: *position,
+                        reason: "unexpected end of input".to_string(),
+                    })?;
+                let name = core::str::from_utf8(name)
+                    .map_err(|_| TypeError::InvalidTypeCode {
+                        position: *position,
+                        reason: "name is not valid UTF-8".to_string(),
+                    })?
+                    .to_string();
+                *position += len;
+                let inner = Self::read_type_code(bytes, position, depth + 1)?;
+                ValueType::Named {
+                    name,
+                    inner: Box::new(inner),
+                }
+            }
+            tag => {
+                return Err(TypeError::InvalidTypeCode {
+                    position: *position - 1,
+                    reason: format!("unknown tag {tag:#04x}"),
+                })
+            }
+        };
+
+        Ok(ty)
+    }
+
+    /// Returns the length of the type in bits, or an error if the length overflows or the
+    /// type contains an array of zero-width elements.
+    fn decodable_len(&self) -> Result<usize, &'static str> {
+        match self {
+            ValueType::Array(elem, len) => {
+                let elem_len = elem.decodable_len()?;
+                if elem_len == 0 {
+                    return Err("array elements have zero width");
+                }
+                elem_len.checked_mul(*len).ok_or("length overflows usize")
+            }
+            ValueType::Struct(fields) => fields.iter().try_fold(0usize, |acc, ty| {
+                acc.checked_add(ty.decodable_len()?)
+                    .ok_or("length overflows usize")
+            }),
+            ValueType::Named { inner, .. } => inner.decodable_len(),
+            ty => Ok(ty.len()),
+        }
+    }
+
+    fn read_type_code_len(bytes: &[u8], position: &mut usize) -> Result<usize, TypeError> {
+        let len =
+            bytes
+                .get(*position..*position + 8)
+                .ok_or_else(|| TypeError::InvalidTypeCode {
+                    position: *position,
+                    reason: "unexpected end of input".to_string(),
+                })?;
+        let len = u64::from_le_bytes(len.try_into().unwrap());
+        let len = usize::try_from(len).map_err(|_| TypeError::InvalidTypeCode {
+            position: *position,
+            reason: format!("length {len} does not fit in usize"),
+        })?;
+        *position += 8;
+
+        Ok(len)
+    }
+
+    /// Validates that the binary representation matches this type.
+    ///
+    /// The structure is compared recursively, and on mismatch the returned error
+    /// contains the path of array indices leading to the offending element.
+    ///
+    /// # Arguments
+    ///
+    /// * `repr` - The binary representation to validate.
+    pub fn validate_repr(&self, repr: &BinaryRepr) -> Result<(), TypeError> {
+        self.validate_repr_at(repr, &mut Vec::new())
+    }
+
+    fn validate_repr_at(&self, repr: &BinaryRepr, path: &mut Vec<usize>) -> Result<(), TypeError> {
+        match (self, repr) {
+            (ValueType::Named { inner, .. }, repr) => inner.validate_repr_at(repr, path),
+            (ValueType::Bit, BinaryRepr::Bit(_))
+            | (ValueType::U8, BinaryRepr::U8(_))
+            | (ValueType::U16, BinaryRepr::U16(_))
+            | (ValueType::U32, BinaryRepr::U32(_))
+            | (ValueType::U64, BinaryRepr::U64(_))
+            | (ValueType::U128, BinaryRepr::U128(_)) => Ok(()),
+            (ValueType::Uint(width), BinaryRepr::Uint(v)) if v.width() == *width => Ok(()),
+            (ValueType::Array(ty, len), BinaryRepr::Array(v)) => {
+                if v.len() != *len {
+                    return Err(TypeError::InvalidRepr {
+                        path: path.clone(),
+                        expected: self.clone(),
+                        actual: ValueType::Array(ty.clone(), v.len()),
+                    });
+                }
+
+                for (i, elem) in v.iter().enumerate() {
+                    path.push(i);
+                    ty.validate_repr_at(elem, path)?;
+                    path.pop();
+                }
+
+                Ok(())
+            }
+            (ValueType::Struct(fields), BinaryRepr::Struct(v)) => {
+                if v.len() != fields.len() {
+                    return Err(TypeError::InvalidRepr {
+                        path: path.clone(),
+                        expected: self.clone(),
+                        actual: repr.value_type(),
+                    });
+                }
+
+                for (i, (ty, elem)) in fields.iter().zip(v).enumerate() {
+                    path.push(i);
+                    ty.validate_repr_at(elem, path)?;
+                    path.pop();
+                }
+
+                Ok(())
+            }
+            _ => Err(TypeError::InvalidRepr {
+                path: path.clone(),
+                expected: self.clone(),
+                actual: repr.value_type(),
+            }),
+        }
     }
 
     pub(crate) fn to_bin_repr(&self, nodes: &[Node<Feed>]) -> Result<BinaryRepr, TypeError> {
         if nodes.len() != self.len() {
-            return Err(TypeError::InvalidLength {
-                expected: self.len(),
-                actual: nodes.len(),
-            });
+            return Err(TypeError::invalid_length(self.len(), nodes.len()));
         }
         let encoded = match self {
             ValueType::Bit => BinaryRepr::Bit(Bit::new(nodes.try_into().unwrap())),
@@ -478,12 +1607,28 @@ impl ValueType {
             ValueType::U32 => BinaryRepr::U32(U32::new(nodes.try_into().unwrap())),
             ValueType::U64 => BinaryRepr::U64(U64::new(nodes.try_into().unwrap())),
             ValueType::U128 => BinaryRepr::U128(U128::new(nodes.try_into().unwrap())),
-            ValueType::Array(ty, _) => BinaryRepr::Array(
-                nodes
-                    .chunks(ty.len())
-                    .map(|nodes| ty.to_bin_repr(nodes).unwrap())
-                    .collect(),
+            ValueType::Uint(_) => BinaryRepr::Uint(Uint::new(nodes.to_vec())),
+            ValueType::Array(_, 0) => return Err(TypeError::ZeroLengthArray),
+            ValueType::Array(ty, len) => BinaryRepr::Array(
+                (0..*len)
+                    .map(|i| ty.to_bin_repr(&nodes[i * ty.len()..(i + 1) * ty.len()]))
+                    .collect::<Result<Vec<_>, _>>()?,
             ),
+            ValueType::Struct(fields) => {
+                let mut offset = 0;
+                BinaryRepr::Struct(
+                    fields
+                        .iter()
+                        .map(|ty| {
+                            let nodes = &nodes[offset..offset + ty.len()];
+                            offset += ty.len();
+                            ty.to_bin_repr(nodes)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+            }
+            // Binary representations do not carry names.
+            ValueType::Named { inner, .. } => inner.to_bin_repr(nodes)?,
         };
 
         Ok(encoded)
@@ -499,11 +1644,96 @@ impl Display for ValueType {
             ValueType::U32 => write!(f, "U32"),
             ValueType::U64 => write!(f, "U64"),
             ValueType::U128 => write!(f, "U128"),
+            ValueType::Uint(width) => write!(f, "Uint<{}>", width),
+            // A length of 0 denotes arrays of any length, see `ValueType::matches`.
+            ValueType::Array(ty, 0) => write!(f, "Array<{}, _>", ty),
             ValueType::Array(ty, len) => write!(f, "Array<{}, {}>", ty, len),
+            ValueType::Struct(fields) => {
+                write!(f, "Struct<")?;
+                for (i, ty) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", ty)?;
+                }
+                write!(f, ">")
+            }
+            ValueType::Named { name, inner } => write!(f, "Named<{}, {}>", name, inner),
+        }
+    }
+}
+
+impl PartialOrd for ValueType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Value types are totally ordered as follows:
+///
+/// - Scalars come before arrays, which come before structs.
+/// - Scalars are ordered by bit width, with the fixed width types coming before a `Uint`
+///   of the same width.
+/// - Arrays are ordered by element type, then by length.
+/// - Structs are ordered lexicographically by their fields.
+/// - Named types are ordered by their inner type, coming after the unnamed type, and
+///   then by name.
+impl Ord for ValueType {
+    fn cmp(&self, other: &Self) -> Ordering {
+        fn rank(ty: &ValueType) -> u8 {
+            match ty {
+                ValueType::Array(..) => 1,
+                ValueType::Struct(..) => 2,
+                _ => 0,
+            }
+        }
+
+        match (self, other) {
+            (
+                ValueType::Named {
+                    name: a_name,
+                    inner: a,
+                },
+                ValueType::Named {
+                    name: b_name,
+                    inner: b,
+                },
+            ) => a.cmp(b).then(a_name.cmp(b_name)),
+            (ValueType::Named { inner, .. }, b) => inner.as_ref().cmp(b).then(Ordering::Greater),
+            (a, ValueType::Named { inner, .. }) => a.cmp(inner).then(Ordering::Less),
+            (ValueType::Array(a, a_len), ValueType::Array(b, b_len)) => {
+                a.cmp(b).then(a_len.cmp(b_len))
+            }
+            (ValueType::Struct(a), ValueType::Struct(b)) => a.cmp(b),
+            (a, b) if a.is_scalar() && b.is_scalar() => a
+                .len()
+                .cmp(&b.len())
+                .then(matches!(a, ValueType::Uint(_)).cmp(&matches!(b, ValueType::Uint(_)))),
+            (a, b) => rank(a).cmp(&rank(b)),
         }
     }
 }
 
+/// Returns the offset of the first bit of each input, given the types of the inputs in
+/// order.
+///
+/// The offset of each input is the sum of the lengths of the preceding inputs, which is
+/// how the inputs of a built circuit are laid out in its feeds.
+///
+/// # Arguments
+///
+/// * `types` - The types of the inputs.
+pub fn input_offsets(types: &[ValueType]) -> Vec<usize> {
+    types
+        .iter()
+        .scan(0, |offset, ty| {
+            let start = *offset;
+            *offset += ty.len();
+            Some(start)
+        })
+        .collect()
+}
+
 macro_rules! impl_value_type {
     ($ty:ty, $ident:ident) => {
         impl StaticValueType for $ty {
@@ -511,12 +1741,6 @@ macro_rules! impl_value_type {
                 ValueType::$ident
             }
         }
-
-        impl<const N: usize> StaticValueType for [$ty; N] {
-            fn value_type() -> ValueType {
-                ValueType::Array(Box::new(ValueType::$ident), N)
-            }
-        }
     };
 }
 
@@ -527,6 +1751,219 @@ impl_value_type!(u32, U32);
 impl_value_type!(u64, U64);
 impl_value_type!(u128, U128);
 
+/// Defines a struct of primitive and array fields which can be used as a value type.
+///
+/// The fields are laid out in declaration order, and the struct is represented as a
+/// [`Value::Struct`] and [`BinaryRepr::Struct`].
+///
+/// # Example
+///
+/// ```
+/// use mpz_circuits::{define_value_struct, types::{BinaryLength, Value}};
+///
+/// define_value_struct!(
+///     #[derive(Debug, Clone, Copy, PartialEq)]
+///     pub struct Header {
+///         pub version: u8,
+///         pub flags: u32,
+///         pub nonce: [u8; 12],
+///     }
+/// );
+///
+/// assert_eq!(Header::LEN, 8 + 32 + 96);
+///
+/// let header = Header { version: 1, flags: 0, nonce: [0u8; 12] };
+/// let value: Value = header.into();
+/// assert_eq!(Header::try_from(value).unwrap(), header);
+/// ```
+#[macro_export]
+macro_rules! define_value_struct {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($field_vis:vis $field:ident : $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($field_vis $field: $ty),*
+        }
+
+        impl $crate::types::BinaryLength for $name {
+            const LEN: usize = 0 $(+ <$ty as $crate::types::BinaryLength>::LEN)*;
+        }
+
+        impl $crate::types::StaticValueType for $name {
+            fn value_type() -> $crate::types::ValueType {
+                $crate::types::ValueType::Struct($crate::__private::vec![
+                    $(<$ty as $crate::types::StaticValueType>::value_type()),*
+                ])
+            }
+        }
+
+        impl $crate::types::ToBinaryRepr for $name {
+            type Repr = $crate::types::BinaryRepr;
+
+            fn len(&self) -> usize {
+                <Self as $crate::types::BinaryLength>::LEN
+            }
+
+            fn new_bin_repr(
+                nodes: &[$crate::Node<$crate::Feed>],
+            ) -> ::core::result::Result<Self::Repr, $crate::types::TypeError> {
+                $crate::types::BinaryRepr::from_nodes(
+                    &<Self as $crate::types::StaticValueType>::value_type(),
+                    nodes,
+                )
+            }
+        }
+
+        impl ::core::convert::From<$name> for $crate::types::Value {
+            fn from(v: $name) -> Self {
+                $crate::types::Value::Struct($crate::__private::vec![$(v.$field.into()),*])
+            }
+        }
+
+        impl ::core::convert::TryFrom<$crate::types::Value> for $name {
+            type Error = $crate::types::TypeError;
+
+            fn try_from(
+                value: $crate::types::Value,
+            ) -> ::core::result::Result<Self, Self::Error> {
+                let expected = <Self as $crate::types::StaticValueType>::value_type();
+                if value.value_type() != expected {
+                    return Err($crate::types::TypeError::UnexpectedType {
+                        expected,
+                        actual: value.value_type(),
+                    });
+                }
+
+                let mut fields = value.into_iter();
+                Ok($name {
+                    $($field: fields.next().expect("field count should match").try_into()?),*
+                })
+            }
+        }
+    };
+}
+
+/// Creates a [`Value`] from a literal using its [`From`] implementations.
+///
+/// Arrays of scalars or of other arrays, which may be nested to any depth, are converted
+/// into the corresponding [`Value::Array`]. As with the [`From`] implementations, all
+/// elements of an array must have the same Rust type, which is checked at compile time.
+///
+/// # Example
+///
+/// ```
+/// use mpz_circuits::{types::Value, value};
+///
+/// assert_eq!(value!(42u32), Value::U32(42));
+/// assert_eq!(
+///     value!([[1u8, 2], [3, 4]]),
+///     Value::Array(vec![
+///         Value::Array(vec![Value::U8(1), Value::U8(2)]),
+///         Value::Array(vec![Value::U8(3), Value::U8(4)]),
+///     ])
+/// );
+/// ```
+#[macro_export]
+macro_rules! value {
+    ($value:expr $(,)?) => {
+        <$crate::types::Value as ::core::convert::From<_>>::from($value)
+    };
+}
+
+/// Asserts that two [`Value`]s are equal.
+///
+/// On failure, this panics with the number of differing bits and the
+/// [structured diff](Value::diff) of the values, listing each differing scalar by its
+/// path, rather than the [`Debug`] representation of both values.
+///
+/// # Example
+///
+/// ```should_panic
+/// use mpz_circuits::{assert_value_eq, types::Value};
+///
+/// // Panics with "assertion `actual == expected` failed, differing bits: 1\n[1]: U8(2) != U8(3)"
+/// assert_value_eq!(Value::from([1u8, 2]), Value::from([1u8, 3]));
+/// ```
+#[macro_export]
+macro_rules! assert_value_eq {
+    ($actual:expr, $expected:expr $(,)?) => {{
+        let (actual, expected): (&$crate::types::Value, &$crate::types::Value) =
+            (&$actual, &$expected);
+        if actual != expected {
+            match (actual.diff(expected), actual.hamming_distance(expected)) {
+                (::core::result::Result::Ok(diff), ::core::result::Result::Ok(bits)) => {
+                    ::core::panic!(
+                        "assertion `actual == expected` failed, differing bits: {}\n{}",
+                        bits,
+                        diff
+                    )
+                }
+                (::core::result::Result::Err(err), _) | (_, ::core::result::Result::Err(err)) => {
+                    ::core::panic!("assertion `actual == expected` failed: {}", err)
+                }
+            }
+        }
+    }};
+}
+
+/// The maximum number of types interned by [`Value::cached_type`] on each thread.
+///
+/// Once the cache is full it is cleared, so types which are still in use are interned
+/// again on their next lookup.
+#[cfg(feature = "std")]
+pub const MAX_CACHED_TYPES: usize = 4096;
+
+/// An interned value type, compared by address.
+///
+/// Holding the [`Arc`] keeps the address from being reused while the key exists.
+#[cfg(feature = "std")]
+struct Interned(Arc<ValueType>);
+
+#[cfg(feature = "std")]
+impl PartialEq for Interned {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Eq for Interned {}
+
+#[cfg(feature = "std")]
+impl core::hash::Hash for Interned {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state)
+    }
+}
+
+/// Key of an interned value type.
+///
+/// Aggregate types are keyed by their interned element types, so looking up the type
+/// of an array does not depend on its length.
+#[cfg(feature = "std")]
+#[derive(PartialEq, Eq, Hash)]
+enum TypeKey {
+    Scalar(ValueType),
+    Array(Interned, usize),
+    Struct(Vec<Interned>),
+    Named(String, Interned),
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    static TYPE_CACHE: RefCell<HashMap<TypeKey, Arc<ValueType>>> = RefCell::new(HashMap::new());
+}
+
+/// The format version written by [`Value::encode_versioned`].
+///
+/// This is bumped whenever the encoding changes in a way that older decoders would
+/// misinterpret, such as when a new variant is added to [`ValueType`].
+pub const VALUE_ENCODING_VERSION: u8 = 1;
+
 /// A value that can be encoded into a binary representation.
 #[derive(Debug, Clone, PartialEq)]
 #[non_exhaustive]
@@ -538,7 +1975,16 @@ pub enum Value {
     U32(u32),
     U64(u64),
     U128(u128),
+    /// An unsigned integer of arbitrary width, stored as its bits in lsb0 order.
+    Uint(Vec<bool>),
     Array(Vec<Value>),
+    /// A structure of heterogeneous fields, laid out in order.
+    Struct(Vec<Value>),
+    /// A value with a semantic label, laid out identically to its inner value.
+    Named {
+        name: String,
+        inner: Box<Value>,
+    },
 }
 
 impl Value {
@@ -551,14 +1997,35 @@ impl Value {
             ValueType::U32 => Value::U32(rng.gen()),
             ValueType::U64 => Value::U64(rng.gen()),
             ValueType::U128 => Value::U128(rng.gen()),
+            ValueType::Uint(width) => Value::Uint((0..*width).map(|_| rng.gen()).collect()),
             ValueType::Array(ty, len) => Value::Array(
                 (0..*len)
                     .map(|_| Value::random(rng, ty))
                     .collect::<Vec<_>>(),
             ),
+            ValueType::Struct(fields) => {
+                Value::Struct(fields.iter().map(|ty| Value::random(rng, ty)).collect())
+            }
+            ValueType::Named { name, inner } => Value::Named {
+                name: name.clone(),
+                inner: Box::new(Value::random(rng, inner)),
+            },
         }
     }
 
+    /// Returns whether the value is an unsigned integer, see [`ValueType::is_integer`].
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            Value::U8(_)
+                | Value::U16(_)
+                | Value::U32(_)
+                | Value::U64(_)
+                | Value::U128(_)
+                | Value::Uint(_)
+        )
+    }
+
     /// Returns the type of the value.
     pub fn value_type(&self) -> ValueType {
         match self {
@@ -568,228 +2035,6375 @@ impl Value {
             Value::U32(_) => ValueType::U32,
             Value::U64(_) => ValueType::U64,
             Value::U128(_) => ValueType::U128,
+            Value::Uint(v) => ValueType::Uint(v.len()),
             Value::Array(v) => ValueType::Array(Box::new(v[0].value_type()), v.len()),
+            Value::Struct(v) => ValueType::Struct(v.iter().map(|v| v.value_type()).collect()),
+            Value::Named { name, inner } => ValueType::Named {
+                name: name.clone(),
+                inner: Box::new(inner.value_type()),
+            },
         }
     }
-}
 
-impl IntoBits for Value {
-    type IterLsb0 = std::vec::IntoIter<bool>;
-    type IterMsb0 = std::vec::IntoIter<bool>;
+    /// Returns the type of the value, interned in a thread-local cache.
+    ///
+    /// Unlike [`Value::value_type`], this does not rebuild the type once it has been seen
+    /// on the current thread, and the returned type is cheap to clone. Looking up the
+    /// type of an array only visits its first element, and structs and named values
+    /// only allocate a small key for their fields or name. Repeated calls for values of
+    /// the same type return the same [`Arc`], unless the cache has been cleared in
+    /// between, see [`MAX_CACHED_TYPES`].
+    #[cfg(feature = "std")]
+    pub fn cached_type(&self) -> Arc<ValueType> {
+        let key = match self {
+            Value::Array(v) => TypeKey::Array(Interned(v[0].cached_type()), v.len()),
+            Value::Struct(v) => {
+                TypeKey::Struct(v.iter().map(|v| Interned(v.cached_type())).collect())
+            }
+            Value::Named { name, inner } => {
+                TypeKey::Named(name.clone(), Interned(inner.cached_type()))
+            }
+            v => TypeKey::Scalar(v.value_type()),
+        };
 
-    fn into_iter_lsb0(self) -> Self::IterLsb0 {
+        TYPE_CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if let Some(ty) = cache.get(&key) {
+                return ty.clone();
+            }
+
+            if cache.len() >= MAX_CACHED_TYPES {
+                cache.clear();
+            }
+
+            let ty = Arc::new(match &key {
+                TypeKey::Scalar(ty) => ty.clone(),
+                TypeKey::Array(elem, len) => ValueType::Array(Box::new((*elem.0).clone()), *len),
+                TypeKey::Struct(fields) => {
+                    ValueType::Struct(fields.iter().map(|ty| (*ty.0).clone()).collect())
+                }
+                TypeKey::Named(name, inner) => ValueType::Named {
+                    name: name.clone(),
+                    inner: Box::new((*inner.0).clone()),
+                },
+            });
+            cache.insert(key, ty.clone());
+            ty
+        })
+    }
+
+    /// Creates a `Uint` value of the given width from its little endian byte representation.
+    ///
+    /// The number of bytes must be exactly the width rounded up to the nearest byte, and
+    /// any padding bits in the most significant byte must be zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the integer in bits.
+    /// * `bytes` - The little endian bytes of the integer.
+    pub fn uint_from_le_bytes(width: usize, bytes: &[u8]) -> Result<Value, TypeError> {
+        let expected = width.div_ceil(8);
+        if bytes.len() != expected {
+            return Err(TypeError::invalid_length(expected, bytes.len()));
+        }
+
+        let mut bits: Vec<bool> = bytes.iter().flat_map(|b| b.into_lsb0_vec()).collect();
+        if bits[width..].iter().any(|bit| *bit) {
+            return Err(TypeError::invalid_length(
+                width,
+                bits.len() - bits.iter().rev().take_while(|bit| !**bit).count(),
+            ));
+        }
+        bits.truncate(width);
+
+        Ok(Value::Uint(bits))
+    }
+
+    /// Returns the little endian byte representation of a `Uint` value, padding the most
+    /// significant byte with zeros.
+    pub fn uint_to_le_bytes(&self) -> Result<Vec<u8>, TypeError> {
         match self {
-            Value::Bit(v) => v.into_lsb0_vec(),
-            Value::U8(v) => v.into_lsb0_vec(),
-            Value::U16(v) => v.into_lsb0_vec(),
-            Value::U32(v) => v.into_lsb0_vec(),
-            Value::U64(v) => v.into_lsb0_vec(),
-            Value::U128(v) => v.into_lsb0_vec(),
-            Value::Array(v) => v.into_iter().flat_map(|v| v.into_iter_lsb0()).collect(),
+            Value::Uint(v) => Ok(Value::uint_bits_to_le_bytes(v)),
+            v => Err(TypeError::UnexpectedType {
+                expected: ValueType::Uint(v.bit_len()),
+                actual: v.value_type(),
+            }),
         }
-        .into_iter()
     }
 
-    fn into_iter_msb0(self) -> Self::IterMsb0 {
+    fn uint_bits_to_le_bytes(bits: &[bool]) -> Vec<u8> {
+        bits.chunks(8)
+            .map(|bits| u8::from_lsb0_iter(bits.iter().copied()))
+            .collect()
+    }
+
+    /// Returns an iterator over the elements of the value.
+    ///
+    /// For arrays this yields each element, for structs each field, and for scalars this
+    /// yields the value itself.
+    pub fn iter(&self) -> core::slice::Iter<'_, Value> {
         match self {
-            Value::Bit(v) => v.into_msb0_vec(),
-            Value::U8(v) => v.into_msb0_vec(),
-            Value::U16(v) => v.into_msb0_vec(),
-            Value::U32(v) => v.into_msb0_vec(),
-            Value::U64(v) => v.into_msb0_vec(),
-            Value::U128(v) => v.into_msb0_vec(),
-            Value::Array(v) => v.into_iter().flat_map(|v| v.into_iter_msb0()).collect(),
+            Value::Array(v) | Value::Struct(v) => v.iter(),
+            v => core::slice::from_ref(v).iter(),
         }
-        .into_iter()
     }
-}
 
-impl Display for Value {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    /// Returns the elements of an array value.
+    ///
+    /// Unlike iterating over the value, this returns an error if the value is not an array.
+    pub fn into_elements(self) -> Result<Vec<Value>, TypeError> {
         match self {
-            Value::Bit(v) => write!(f, "Bit({})", v),
-            Value::U8(v) => write!(f, "U8({})", v),
-            Value::U16(v) => write!(f, "U16({})", v),
-            Value::U32(v) => write!(f, "U32({})", v),
-            Value::U64(v) => write!(f, "U64({})", v),
-            Value::U128(v) => write!(f, "U128({})", v),
-            Value::Array(v) => write!(f, "Array({:?})", v),
+            Value::Array(v) => Ok(v),
+            v => Err(TypeError::UnexpectedType {
+                expected: ValueType::Array(Box::new(v.value_type()), 0),
+                actual: v.value_type(),
+            }),
         }
     }
-}
 
-impl BitXor for Value {
-    type Output = Result<Value, TypeError>;
+    /// Returns a reference to the elements of an array value.
+    ///
+    /// Unlike [`Value::iter`], this returns an error if the value is not an array.
+    pub fn as_elements(&self) -> Result<&[Value], TypeError> {
+        match self {
+            Value::Array(v) => Ok(v),
+            v => Err(TypeError::UnexpectedType {
+                expected: ValueType::Array(Box::new(v.value_type()), 0),
+                actual: v.value_type(),
+            }),
+        }
+    }
 
-    fn bitxor(self, rhs: Self) -> Self::Output {
-        Ok(match (&self, &rhs) {
-            (Value::Bit(a), Value::Bit(b)) => Value::Bit(a ^ b),
-            (Value::U8(a), Value::U8(b)) => Value::U8(a ^ b),
-            (Value::U16(a), Value::U16(b)) => Value::U16(a ^ b),
-            (Value::U32(a), Value::U32(b)) => Value::U32(a ^ b),
-            (Value::U64(a), Value::U64(b)) => Value::U64(a ^ b),
-            (Value::U128(a), Value::U128(b)) => Value::U128(a ^ b),
-            (Value::Array(a), Value::Array(b)) => Value::Array(
-                a.iter()
-                    .zip(b.iter())
-                    .map(|(a, b)| a ^ b)
-                    .collect::<Result<Vec<_>, _>>()?,
-            ),
-            _ => {
-                return Err(TypeError::UnexpectedType {
-                    expected: self.value_type(),
-                    actual: rhs.value_type(),
-                })
-            }
-        })
+    /// Returns `true` if every element of an array value is equal.
+    ///
+    /// Elements are compared in full, so nested arrays are equal only if all of their own
+    /// elements are. An empty array returns `true`.
+    ///
+    /// Returns an error if the value is not an array.
+    pub fn elements_all_equal(&self) -> Result<bool, TypeError> {
+        let elems = self.as_elements()?;
+
+        Ok(elems.windows(2).all(|w| w[0] == w[1]))
     }
-}
 
-impl BitXor<&Value> for &Value {
-    type Output = Result<Value, TypeError>;
+    /// Checks that every array within the value has elements of a single type, returning
+    /// a copy of the value.
+    ///
+    /// Every well-typed value already has a single representation, so a valid value is
+    /// returned unchanged. This is intended as a post-condition check for code which
+    /// constructs values leaf by leaf.
+    ///
+    /// Returns an error for the first element whose type differs from that of the first
+    /// element of its array.
+    pub fn canonicalize(&self) -> Result<Value, TypeError> {
+        self.check_canonical()?;
+        Ok(self.clone())
+    }
 
-    fn bitxor(self, rhs: &Value) -> Self::Output {
-        Ok(match (self, rhs) {
-            (Value::Bit(a), Value::Bit(b)) => Value::Bit(a ^ b),
-            (Value::U8(a), Value::U8(b)) => Value::U8(a ^ b),
-            (Value::U16(a), Value::U16(b)) => Value::U16(a ^ b),
-            (Value::U32(a), Value::U32(b)) => Value::U32(a ^ b),
-            (Value::U64(a), Value::U64(b)) => Value::U64(a ^ b),
-            (Value::U128(a), Value::U128(b)) => Value::U128(a ^ b),
-            (Value::Array(a), Value::Array(b)) => Value::Array(
-                a.iter()
-                    .zip(b.iter())
-                    .map(|(a, b)| a ^ b)
-                    .collect::<Result<Vec<_>, _>>()?,
-            ),
-            _ => {
-                return Err(TypeError::UnexpectedType {
-                    expected: self.value_type(),
-                    actual: rhs.value_type(),
-                })
+    fn check_canonical(&self) -> Result<(), TypeError> {
+        match self {
+            Value::Array(v) => {
+                let expected = v.first().map(|v| v.value_type());
+                for (index, elem) in v.iter().enumerate() {
+                    let in_element = |source| TypeError::ArrayElement {
+                        index,
+                        source: Box::new(source),
+                    };
+                    elem.check_canonical().map_err(in_element)?;
+
+                    let actual = elem.value_type();
+                    if let Some(expected) = expected.as_ref().filter(|ty| **ty != actual) {
+                        return Err(in_element(TypeError::UnexpectedType {
+                            expected: expected.clone(),
+                            actual,
+                        }));
+                    }
+                }
+                Ok(())
             }
-        })
+            Value::Struct(v) => v.iter().try_for_each(|v| v.check_canonical()),
+            Value::Named { inner, .. } => inner.check_canonical(),
+            _ => Ok(()),
+        }
     }
-}
 
-impl BitXor<&Value> for Value {
-    type Output = Result<Value, TypeError>;
+    /// Creates an array value by repeating `elem` `len` times.
+    ///
+    /// `elem` may itself be an array or a struct, in which case the result is a nested
+    /// array.
+    ///
+    /// # Arguments
+    ///
+    /// * `elem` - The element to repeat.
+    /// * `len` - The length of the array.
+    pub fn splat(elem: Value, len: usize) -> Result<Value, TypeError> {
+        if len == 0 {
+            return Err(TypeError::ZeroLengthArray);
+        }
 
-    fn bitxor(self, rhs: &Value) -> Self::Output {
-        Ok(match (&self, rhs) {
-            (Value::Bit(a), Value::Bit(b)) => Value::Bit(a ^ b),
-            (Value::U8(a), Value::U8(b)) => Value::U8(a ^ b),
-            (Value::U16(a), Value::U16(b)) => Value::U16(a ^ b),
-            (Value::U32(a), Value::U32(b)) => Value::U32(a ^ b),
-            (Value::U64(a), Value::U64(b)) => Value::U64(a ^ b),
-            (Value::U128(a), Value::U128(b)) => Value::U128(a ^ b),
-            (Value::Array(a), Value::Array(b)) => Value::Array(
-                a.iter()
-                    .zip(b.iter())
-                    .map(|(a, b)| a ^ b)
-                    .collect::<Result<Vec<_>, _>>()?,
-            ),
-            _ => {
-                return Err(TypeError::UnexpectedType {
-                    expected: self.value_type(),
-                    actual: rhs.value_type(),
-                })
-            }
-        })
+        Ok(Value::Array(vec![elem; len]))
     }
-}
 
-impl BitXor<Value> for &Value {
-    type Output = Result<Value, TypeError>;
+    /// Creates a value of the target type by repeating the lsb0 bits of `pattern` until
+    /// they fill the type.
+    ///
+    /// For example, tiling `U8(0xaa)` across a `U32` gives `U32(0xaaaaaaaa)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern to repeat.
+    /// * `target` - The type to fill, whose length must be a multiple of the length of
+    ///   the pattern.
+    pub fn repeat_pattern(pattern: &Value, target: &ValueType) -> Result<Value, TypeError> {
+        let width = pattern.bit_len();
+        if width == 0 {
+            return Err(TypeError::invalid_length(target.len(), 0));
+        }
 
-    fn bitxor(self, rhs: Value) -> Self::Output {
-        Ok(match (self, &rhs) {
-            (Value::Bit(a), Value::Bit(b)) => Value::Bit(a ^ b),
-            (Value::U8(a), Value::U8(b)) => Value::U8(a ^ b),
-            (Value::U16(a), Value::U16(b)) => Value::U16(a ^ b),
-            (Value::U32(a), Value::U32(b)) => Value::U32(a ^ b),
-            (Value::U64(a), Value::U64(b)) => Value::U64(a ^ b),
-            (Value::U128(a), Value::U128(b)) => Value::U128(a ^ b),
-            (Value::Array(a), Value::Array(b)) => Value::Array(
-                a.iter()
-                    .zip(b.iter())
-                    .map(|(a, b)| a ^ b)
-                    .collect::<Result<Vec<_>, _>>()?,
-            ),
-            _ => {
-                return Err(TypeError::UnexpectedType {
-                    expected: self.value_type(),
-                    actual: rhs.value_type(),
-                })
-            }
-        })
+        if !target.len().is_multiple_of(width) {
+            return Err(TypeError::invalid_length(
+                target.len().next_multiple_of(width),
+                target.len(),
+            ));
+        }
+
+        let pattern: Vec<bool> = pattern.clone().into_iter_lsb0().collect();
+        let bits: Vec<bool> = pattern.iter().copied().cycle().take(target.len()).collect();
+
+        Ok(Value::decode_lsb0(target, &bits))
     }
-}
 
-macro_rules! impl_convert_bytes {
-    ($ty:ident, $len:expr) => {
-        impl $ty {
-            /// Create a value from its representation as a byte array in big endian.
-            pub fn from_be_bytes(bytes: [U8; $len]) -> Self {
-                $ty(std::array::from_fn(|i| bytes[$len - (i / 8) - 1].0[i % 8]))
+    /// Creates an array value of the given length from a sparse set of entries, with
+    /// every other element set to `fill`.
+    ///
+    /// Later entries overwrite earlier entries with the same index.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The length of the array.
+    /// * `fill` - The value of the elements without an entry.
+    /// * `entries` - The index and value of each entry, which must have the same type as `fill`.
+    pub fn from_sparse(
+        len: usize,
+        fill: Value,
+        entries: impl IntoIterator<Item = (usize, Value)>,
+    ) -> Result<Value, TypeError> {
+        if len == 0 {
+            return Err(TypeError::ZeroLengthArray);
+        }
+
+        let expected = fill.value_type();
+        let mut v = vec![fill; len];
+        for (index, value) in entries {
+            if index >= len {
+                return Err(TypeError::invalid_length(len, index.saturating_add(1)));
             }
 
-            /// Returns the representation of this type as a byte array in big endian.
-            pub fn to_be_bytes(self) -> [U8; $len] {
-                std::array::from_fn(|i| U8(std::array::from_fn(|j| self.0[($len - i - 1) * 8 + j])))
+            let actual = value.value_type();
+            if actual != expected {
+                return Err(TypeError::ArrayElement {
+                    index,
+                    source: Box::new(TypeError::UnexpectedType { expected, actual }),
+                });
             }
 
-            /// Create a value from its representation as a byte array in little endian.
-            pub fn from_le_bytes(bytes: [U8; $len]) -> Self {
-                $ty(std::array::from_fn(|i| bytes[i / 8].0[i % 8]))
+            v[index] = value;
+        }
+
+        Ok(Value::Array(v))
+    }
+
+    /// Resizes an array value to the given length, truncating it or extending it with
+    /// copies of `fill`.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_len` - The length of the resized array.
+    /// * `fill` - The value to extend the array with, which must match its element type.
+    pub fn resize_array(&self, new_len: usize, fill: Value) -> Result<Value, TypeError> {
+        let Value::Array(v) = self else {
+            return Err(TypeError::UnexpectedType {
+                expected: ValueType::Array(Box::new(fill.value_type()), new_len),
+                actual: self.value_type(),
+            });
+        };
+
+        if new_len == 0 {
+            return Err(TypeError::ZeroLengthArray);
+        }
+
+        let (expected, actual) = (v[0].value_type(), fill.value_type());
+        if expected != actual {
+            return Err(TypeError::UnexpectedType { expected, actual });
+        }
+
+        let mut v = v.clone();
+        v.resize(new_len, fill);
+
+        Ok(Value::Array(v))
+    }
+
+    /// Rotates the elements of an array value to the left by `n` positions, so that the
+    /// element at index `n` becomes the first.
+    ///
+    /// `n` may be larger than the length of the array, in which case it wraps around.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of positions to rotate by.
+    pub fn rotate_array_left(&self, n: usize) -> Result<Value, TypeError> {
+        let mut v = self.as_elements()?.to_vec();
+        let n = n % v.len().max(1);
+        v.rotate_left(n);
+
+        Ok(Value::Array(v))
+    }
+
+    /// Rotates the elements of an array value to the right by `n` positions, so that the
+    /// last element moves to index `n - 1`.
+    ///
+    /// `n` may be larger than the length of the array, in which case it wraps around.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of positions to rotate by.
+    pub fn rotate_array_right(&self, n: usize) -> Result<Value, TypeError> {
+        let mut v = self.as_elements()?.to_vec();
+        let n = n % v.len().max(1);
+        v.rotate_right(n);
+
+        Ok(Value::Array(v))
+    }
+
+    /// Overwrites the elements of an array value starting at `start` with the elements
+    /// of another array, in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first element to overwrite.
+    /// * `replacement` - The array to copy elements from, which must have the same
+    ///   element type and fit within the array.
+    pub fn splice(&mut self, start: usize, replacement: &Value) -> Result<(), TypeError> {
+        let (Value::Array(v), Value::Array(replacement)) = (&mut *self, replacement) else {
+            return Err(TypeError::UnexpectedType {
+                expected: self.value_type(),
+                actual: replacement.value_type(),
+            });
+        };
+
+        // An end past `usize::MAX` is reported as `usize::MAX`.
+        let end = match start.checked_add(replacement.len()) {
+            Some(end) if end <= v.len() => end,
+            end => {
+                return Err(TypeError::invalid_length(
+                    v.len(),
+                    end.unwrap_or(usize::MAX),
+                ))
             }
+        };
 
-            /// Returns the representation of this type as a byte array in little endian.
-            pub fn to_le_bytes(self) -> [U8; $len] {
-                std::array::from_fn(|i| U8(std::array::from_fn(|j| self.0[i * 8 + j])))
+        if let Some(first) = replacement.first() {
+            let (expected, actual) = (v[0].value_type(), first.value_type());
+            if expected != actual {
+                return Err(TypeError::UnexpectedType { expected, actual });
             }
         }
-    };
-}
 
-impl_convert_bytes!(U8, 1);
-impl_convert_bytes!(U16, 2);
-impl_convert_bytes!(U32, 4);
-impl_convert_bytes!(U64, 8);
-impl_convert_bytes!(U128, 16);
+        v[start..end].clone_from_slice(replacement);
 
-#[cfg(test)]
-mod tests {
-    use mpz_circuits_macros::{test_circ, trace};
+        Ok(())
+    }
 
-    use crate::CircuitBuilder;
+    /// Returns whether every bit of the value is zero.
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Value::Bit(v) => !*v,
+            Value::U8(v) => *v == 0,
+            Value::U16(v) => *v == 0,
+            Value::U32(v) => *v == 0,
+            Value::U64(v) => *v == 0,
+            Value::U128(v) => *v == 0,
+            Value::Uint(v) => v.iter().all(|bit| !*bit),
+            Value::Array(v) | Value::Struct(v) => v.iter().all(|v| v.is_zero()),
+            Value::Named { inner, .. } => inner.is_zero(),
+        }
+    }
 
-    #[trace]
-    fn to_be_bytes(a: u128) -> [u8; 16] {
-        a.to_be_bytes()
+    /// Returns whether every bit of the value is one.
+    pub fn is_all_ones(&self) -> bool {
+        match self {
+            Value::Bit(v) => *v,
+            Value::U8(v) => *v == u8::MAX,
+            Value::U16(v) => *v == u16::MAX,
+            Value::U32(v) => *v == u32::MAX,
+            Value::U64(v) => *v == u64::MAX,
+            Value::U128(v) => *v == u128::MAX,
+            Value::Uint(v) => v.iter().all(|bit| *bit),
+            Value::Array(v) | Value::Struct(v) => v.iter().all(|v| v.is_all_ones()),
+            Value::Named { inner, .. } => inner.is_all_ones(),
+        }
     }
 
-    #[trace]
-    fn to_le_bytes(a: u128) -> [u8; 16] {
-        a.to_le_bytes()
+    /// Returns the number of leading zeros of an integer value.
+    ///
+    /// Zeros are counted within the full width of the variant, so
+    /// `Value::U32(1).leading_zeros()` is 31 and a zero value returns its width.
+    pub fn leading_zeros(&self) -> Result<u32, TypeError> {
+        match self {
+            Value::U8(v) => Ok(v.leading_zeros()),
+            Value::U16(v) => Ok(v.leading_zeros()),
+            Value::U32(v) => Ok(v.leading_zeros()),
+            Value::U64(v) => Ok(v.leading_zeros()),
+            Value::U128(v) => Ok(v.leading_zeros()),
+            Value::Uint(v) => Ok(v.iter().rev().take_while(|bit| !**bit).count() as u32),
+            v => Err(TypeError::NotInteger {
+                actual: v.value_type(),
+            }),
+        }
     }
 
-    #[test]
-    fn test_convert_bytes() {
-        let builder = CircuitBuilder::new();
-        let a = builder.add_input::<u128>();
-        let a_bytes = to_be_bytes_trace(builder.state(), a);
-        builder.add_output(a_bytes);
-        let circ = builder.build().unwrap();
+    /// Returns the number of trailing zeros of an integer value.
+    ///
+    /// Zeros are counted within the full width of the variant, so a zero value returns
+    /// its width.
+    pub fn trailing_zeros(&self) -> Result<u32, TypeError> {
+        match self {
+            Value::U8(v) => Ok(v.trailing_zeros()),
+            Value::U16(v) => Ok(v.trailing_zeros()),
+            Value::U32(v) => Ok(v.trailing_zeros()),
+            Value::U64(v) => Ok(v.trailing_zeros()),
+            Value::U128(v) => Ok(v.trailing_zeros()),
+            Value::Uint(v) => Ok(v.iter().take_while(|bit| !**bit).count() as u32),
+            v => Err(TypeError::NotInteger {
+                actual: v.value_type(),
+            }),
+        }
+    }
 
-        test_circ!(circ, to_be_bytes, fn(69u128) -> [u8; 16]);
+    /// Returns the length of the value in bits.
+    ///
+    /// This is equivalent to `self.value_type().len()` without constructing the type.
+    pub fn bit_len(&self) -> usize {
+        match self {
+            Value::Bit(_) => 1,
+            Value::U8(_) => 8,
+            Value::U16(_) => 16,
+            Value::U32(_) => 32,
+            Value::U64(_) => 64,
+            Value::U128(_) => 128,
+            Value::Uint(v) => v.len(),
+            Value::Array(v) | Value::Struct(v) => v.iter().map(|v| v.bit_len()).sum(),
+            Value::Named { inner, .. } => inner.bit_len(),
+        }
+    }
 
-        let builder = CircuitBuilder::new();
-        let a = builder.add_input::<u128>();
-        let a_bytes = to_le_bytes_trace(builder.state(), a);
-        builder.add_output(a_bytes);
-        let circ = builder.build().unwrap();
+    /// Returns the length of the value in bytes, rounded up to the nearest byte.
+    pub fn byte_len(&self) -> usize {
+        self.bit_len().div_ceil(8)
+    }
 
-        test_circ!(circ, to_le_bytes, fn(69u128) -> [u8; 16]);
+    /// Converts the value into its bits in the given order.
+    ///
+    /// Unlike `Vec::<bool>::try_from(value)`, which expects an array of `Bit` values,
+    /// this accepts a value of any type and does not require [`IntoBits`] to be in scope.
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The order of the bits within each scalar.
+    pub fn into_bits_vec(self, order: BitOrder) -> Vec<bool> {
+        match order {
+            BitOrder::Lsb0 => self.into_iter_lsb0().collect(),
+            BitOrder::Msb0 => self.into_iter_msb0().collect(),
+        }
+    }
+
+    /// Returns the bits of the value in the given order, padded with zeros up to the
+    /// next byte boundary.
+    ///
+    /// The padding is appended after the bits of the value, so the returned length is
+    /// always `8 * self.byte_len()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The order of the bits within each scalar.
+    pub fn to_bits_padded(&self, order: BitOrder) -> Vec<bool> {
+        let mut bits = self.clone().into_bits_vec(order);
+        bits.resize(self.byte_len() * 8, false);
+        bits
+    }
+
+    /// Returns the bits of the value in the given order, packed into bytes.
+    ///
+    /// Unlike [`Value::to_bits_padded`], this never pads the value, and instead returns an
+    /// error if its bit length is not a multiple of 8.
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The order of the bits within each scalar, and within each byte.
+    pub fn to_bytes_exact(&self, order: BitOrder) -> Result<Vec<u8>, TypeError> {
+        let bit_len = self.bit_len();
+        if !bit_len.is_multiple_of(8) {
+            return Err(TypeError::NotByteAligned { bit_len });
+        }
+
+        Ok(self
+            .clone()
+            .into_bits_vec(order)
+            .chunks(8)
+            .map(|bits| match order {
+                BitOrder::Lsb0 => u8::from_lsb0_iter(bits.iter().copied()),
+                BitOrder::Msb0 => u8::from_msb0_iter(bits.iter().copied()),
+            })
+            .collect())
+    }
+
+    /// XORs the bits of the value with a mask, returning a value of the same type.
+    ///
+    /// The mask is applied to the flattened bits of the value, so it must have exactly
+    /// `self.bit_len()` bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - The bits to XOR with.
+    /// * `order` - The order of the bits within each scalar, for both the value and the mask.
+    pub fn xor_bits(&self, mask: &[bool], order: BitOrder) -> Result<Value, TypeError> {
+        let len = self.bit_len();
+        if mask.len() != len {
+            return Err(TypeError::invalid_length(len, mask.len()));
+        }
+
+        let bits = self
+            .clone()
+            .into_bits_vec(order)
+            .into_iter()
+            .zip(mask)
+            .map(|(bit, mask)| bit ^ mask);
+
+        Value::from_bits(&self.value_type(), bits, order)
+    }
+
+    /// Returns the positions of the set bits of the value, in ascending order.
+    ///
+    /// This is the inverse of [`Value::from_set_indices`].
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The order of the bits within each scalar.
+    pub fn set_bit_indices(&self, order: BitOrder) -> Vec<usize> {
+        self.clone()
+            .into_bits_vec(order)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, bit)| bit.then_some(idx))
+            .collect()
+    }
+
+    /// Creates a value of the given type with exactly the bits at the given positions set.
+    ///
+    /// # Arguments
+    ///
+    /// * `ty` - The type of the value.
+    /// * `indices` - The positions of the set bits, which must be less than `ty.len()`.
+    /// * `order` - The order of the bits within each scalar.
+    pub fn from_set_indices(
+        ty: &ValueType,
+        indices: impl IntoIterator<Item = usize>,
+        order: BitOrder,
+    ) -> Result<Value, TypeError> {
+        let len = ty.len();
+        let mut bits = vec![false; len];
+        for idx in indices {
+            if idx >= len {
+                return Err(TypeError::invalid_length(len, idx.saturating_add(1)));
+            }
+            bits[idx] = true;
+        }
+
+        Value::from_bits(ty, bits, order)
+    }
+
+    /// Returns `a` if `cond` is true, and `b` otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `cond` - The condition, which must be a `Bit`.
+    /// * `a` - The value to select if `cond` is true.
+    /// * `b` - The value to select if `cond` is false, which must have the same type as `a`.
+    pub fn select(cond: &Value, a: &Value, b: &Value) -> Result<Value, TypeError> {
+        let Value::Bit(cond) = cond else {
+            return Err(TypeError::UnexpectedType {
+                expected: ValueType::Bit,
+                actual: cond.value_type(),
+            });
+        };
+
+        Value::check_same_type(a, b)?;
+
+        Ok(if *cond { a.clone() } else { b.clone() })
+    }
+
+    /// Selects each bit from `a` if the corresponding bit of `mask` is set, and from `b`
+    /// otherwise.
+    ///
+    /// The bits are matched up in lsb0 order, so `mask` may have any type with the same
+    /// bit length as `a`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - The selection mask.
+    /// * `a` - The value to select the set bits from.
+    /// * `b` - The value to select the unset bits from, which must have the same type as `a`.
+    pub fn select_bits(mask: &Value, a: &Value, b: &Value) -> Result<Value, TypeError> {
+        Value::check_same_type(a, b)?;
+
+        let len = a.bit_len();
+        if mask.bit_len() != len {
+            return Err(TypeError::invalid_length(len, mask.bit_len()));
+        }
+
+        let bits: Vec<bool> = mask
+            .clone()
+            .into_iter_lsb0()
+            .zip(a.clone().into_iter_lsb0())
+            .zip(b.clone().into_iter_lsb0())
+            .map(|((mask, a), b)| if mask { a } else { b })
+            .collect();
+
+        Ok(Value::decode_lsb0(&a.value_type(), &bits))
+    }
+
+    fn check_same_type(a: &Value, b: &Value) -> Result<(), TypeError> {
+        let (expected, actual) = (a.value_type(), b.value_type());
+        if expected != actual {
+            return Err(TypeError::UnexpectedType { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Converts an array value into a matrix of bits over GF(2), with one row per element.
+    ///
+    /// This is the inverse of [`Value::from_bit_matrix`].
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The order of the bits within each row.
+    pub fn to_bit_matrix(&self, order: BitOrder) -> Result<Vec<Vec<bool>>, TypeError> {
+        Ok(self
+            .as_elements()?
+            .iter()
+            .map(|v| v.clone().into_bits_vec(order))
+            .collect())
+    }
+
+    /// Creates an array value from a matrix of bits over GF(2), with one element per row.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The rows of the matrix, each of which must have `elem_ty.len()` bits.
+    /// * `elem_ty` - The type of the elements.
+    /// * `order` - The order of the bits within each row.
+    pub fn from_bit_matrix(
+        rows: &[Vec<bool>],
+        elem_ty: &ValueType,
+        order: BitOrder,
+    ) -> Result<Value, TypeError> {
+        if rows.is_empty() {
+            return Err(TypeError::ZeroLengthArray);
+        }
+
+        rows.iter()
+            .enumerate()
+            .map(|(index, row)| {
+                Value::from_bits(elem_ty, row.iter().copied(), order).map_err(|e| {
+                    TypeError::ArrayElement {
+                        index,
+                        source: Box::new(e),
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array)
+    }
+
+    /// Returns whether two values are equal, ignoring the order of array elements.
+    ///
+    /// Arrays are compared as multisets, recursing into nested arrays, and scalars
+    /// are compared using `==`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The value to compare with.
+    ///
+    /// # Returns
+    ///
+    /// Whether the values are equal, or an error if the types of the values differ.
+    pub fn eq_unordered(&self, other: &Value) -> Result<bool, TypeError> {
+        let (expected, actual) = (self.value_type(), other.value_type());
+        if expected != actual {
+            return Err(TypeError::UnexpectedType { expected, actual });
+        }
+
+        Ok(self.eq_unordered_inner(other))
+    }
+
+    fn eq_unordered_inner(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Array(a), Value::Array(b)) => {
+                let mut used = vec![false; b.len()];
+                a.iter().all(|a| {
+                    let matched = b
+                        .iter()
+                        .enumerate()
+                        .position(|(i, b)| !used[i] && a.eq_unordered_inner(b));
+
+                    if let Some(i) = matched {
+                        used[i] = true;
+                        true
+                    } else {
+                        false
+                    }
+                })
+            }
+            (Value::Struct(a), Value::Struct(b)) => {
+                a.iter().zip(b).all(|(a, b)| a.eq_unordered_inner(b))
+            }
+            (Value::Named { inner: a, .. }, Value::Named { inner: b, .. }) => {
+                a.eq_unordered_inner(b)
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Applies `f` to each pair of corresponding scalar leaves of two values with
+    /// identical shape, returning a value of the same shape.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The value to combine with.
+    /// * `f` - The function to apply to each pair of leaves.
+    ///
+    /// # Returns
+    ///
+    /// The combined value, or an error at the first point the shapes of the two values diverge.
+    pub fn zip_map<F>(&self, other: &Value, mut f: F) -> Result<Value, TypeError>
+    where
+        F: FnMut(&Value, &Value) -> Result<Value, TypeError>,
+    {
+        self.zip_map_inner(other, &mut f)
+    }
+
+    fn zip_map_inner<F>(&self, other: &Value, f: &mut F) -> Result<Value, TypeError>
+    where
+        F: FnMut(&Value, &Value) -> Result<Value, TypeError>,
+    {
+        match (self, other) {
+            (Value::Array(a), Value::Array(b)) if a.len() == b.len() => Ok(Value::Array(
+                a.iter()
+                    .zip(b)
+                    .map(|(a, b)| a.zip_map_inner(b, f))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            (Value::Struct(a), Value::Struct(b)) if a.len() == b.len() => Ok(Value::Struct(
+                a.iter()
+                    .zip(b)
+                    .map(|(a, b)| a.zip_map_inner(b, f))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            (
+                Value::Named { name, inner: a },
+                Value::Named {
+                    name: b_name,
+                    inner: b,
+                },
+            ) if name == b_name => Ok(Value::Named {
+                name: name.clone(),
+                inner: Box::new(a.zip_map_inner(b, f)?),
+            }),
+            (Value::Array(_) | Value::Struct(_) | Value::Named { .. }, _)
+            | (_, Value::Array(_) | Value::Struct(_) | Value::Named { .. }) => {
+                Err(TypeError::UnexpectedType {
+                    expected: self.value_type(),
+                    actual: other.value_type(),
+                })
+            }
+            _ => {
+                let (expected, actual) = (self.value_type(), other.value_type());
+                if expected != actual {
+                    return Err(TypeError::UnexpectedType { expected, actual });
+                }
+                f(self, other)
+            }
+        }
+    }
+
+    /// Adds two integer values of the same type, checking for overflow.
+    ///
+    /// Arrays and structs are added element-wise.
+    ///
+    /// # Arguments
+    ///
+    /// * `rhs` - The value to add.
+    ///
+    /// # Returns
+    ///
+    /// The sum, or `None` if the addition of any element overflows.
+    pub fn checked_add(&self, rhs: &Value) -> Result<Option<Value>, TypeError> {
+        let mut overflow = false;
+        let sum = self.zip_map(rhs, |a, b| {
+            let sum = match (a, b) {
+                (Value::U8(a), Value::U8(b)) => a.checked_add(*b).map(Value::U8),
+                (Value::U16(a), Value::U16(b)) => a.checked_add(*b).map(Value::U16),
+                (Value::U32(a), Value::U32(b)) => a.checked_add(*b).map(Value::U32),
+                (Value::U64(a), Value::U64(b)) => a.checked_add(*b).map(Value::U64),
+                (Value::U128(a), Value::U128(b)) => a.checked_add(*b).map(Value::U128),
+                (Value::Uint(_), Value::Uint(_)) => {
+                    let (sum, carry) = a.add_with_carry(b, false)?;
+                    (!carry).then_some(sum)
+                }
+                (v, _) => {
+                    return Err(TypeError::NotInteger {
+                        actual: v.value_type(),
+                    })
+                }
+            };
+
+            Ok(sum.unwrap_or_else(|| {
+                overflow = true;
+                a.clone()
+            }))
+        })?;
+
+        Ok((!overflow).then_some(sum))
+    }
+
+    /// Adds two scalar integer values of the same type and a carry bit, returning the
+    /// wrapped sum and the carry-out bit.
+    ///
+    /// # Arguments
+    ///
+    /// * `rhs` - The value to add.
+    /// * `carry_in` - The carry bit to add.
+    pub fn add_with_carry(&self, rhs: &Value, carry_in: bool) -> Result<(Value, bool), TypeError> {
+        macro_rules! add {
+            ($variant:ident, $a:expr, $b:expr) => {{
+                let (sum, carry_a) = $a.overflowing_add(*$b);
+                let (sum, carry_b) = sum.overflowing_add(carry_in.into());
+                (Value::$variant(sum), carry_a | carry_b)
+            }};
+        }
+
+        Ok(match (self, rhs) {
+            (Value::U8(a), Value::U8(b)) => add!(U8, a, b),
+            (Value::U16(a), Value::U16(b)) => add!(U16, a, b),
+            (Value::U32(a), Value::U32(b)) => add!(U32, a, b),
+            (Value::U64(a), Value::U64(b)) => add!(U64, a, b),
+            (Value::U128(a), Value::U128(b)) => add!(U128, a, b),
+            (Value::Uint(a), Value::Uint(b)) if a.len() == b.len() => {
+                let mut carry = carry_in;
+                let sum = a
+                    .iter()
+                    .zip(b)
+                    .map(|(a, b)| {
+                        let bit = a ^ b ^ carry;
+                        carry = (a & b) | (carry & (a ^ b));
+                        bit
+                    })
+                    .collect();
+                (Value::Uint(sum), carry)
+            }
+            (Value::Bit(_) | Value::Array(_) | Value::Struct(_) | Value::Named { .. }, _) => {
+                return Err(TypeError::NotInteger {
+                    actual: self.value_type(),
+                })
+            }
+            _ => {
+                return Err(TypeError::UnexpectedType {
+                    expected: self.value_type(),
+                    actual: rhs.value_type(),
+                })
+            }
+        })
+    }
+
+    /// Multiplies two scalar integer values of the same type, returning the full product
+    /// as an integer of twice the width.
+    ///
+    /// Fixed width integers are widened to the next larger variant, eg. `U32 * U32` gives
+    /// a `U64`, and a `Uint` of width `n` gives a `Uint` of width `2n`. `U128` values are
+    /// not supported as there is no fixed width variant wide enough for the product, and
+    /// return [`TypeError::NoWiderType`].
+    ///
+    /// # Arguments
+    ///
+    /// * `rhs` - The value to multiply by.
+    pub fn widening_mul(&self, rhs: &Value) -> Result<Value, TypeError> {
+        Ok(match (self, rhs) {
+            (Value::U8(a), Value::U8(b)) => Value::U16(*a as u16 * *b as u16),
+            (Value::U16(a), Value::U16(b)) => Value::U32(*a as u32 * *b as u32),
+            (Value::U32(a), Value::U32(b)) => Value::U64(*a as u64 * *b as u64),
+            (Value::U64(a), Value::U64(b)) => Value::U128(*a as u128 * *b as u128),
+            (Value::Uint(a), Value::Uint(b)) if a.len() == b.len() => {
+                let width = a.len();
+                let mut product = vec![false; 2 * width];
+                for (i, _) in a.iter().enumerate().filter(|(_, bit)| **bit) {
+                    // Add `b << i` to the product.
+                    let mut carry = false;
+                    for j in 0..2 * width - i {
+                        let p = product[i + j];
+                        let bit = j < width && b[j];
+                        product[i + j] = p ^ bit ^ carry;
+                        carry = (p & bit) | (carry & (p ^ bit));
+                    }
+                }
+                Value::Uint(product)
+            }
+            (Value::U128(_), Value::U128(_)) => {
+                return Err(TypeError::NoWiderType {
+                    ty: ValueType::U128,
+                })
+            }
+            (Value::Bit(_) | Value::Array(_) | Value::Struct(_) | Value::Named { .. }, _) => {
+                return Err(TypeError::NotInteger {
+                    actual: self.value_type(),
+                })
+            }
+            _ => {
+                return Err(TypeError::UnexpectedType {
+                    expected: self.value_type(),
+                    actual: rhs.value_type(),
+                })
+            }
+        })
+    }
+
+    /// Divides two scalar integer values of the same type, returning the quotient.
+    ///
+    /// Returns an error if `rhs` is zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `rhs` - The value to divide by.
+    pub fn checked_div(&self, rhs: &Value) -> Result<Value, TypeError> {
+        self.div_rem(rhs).map(|(quotient, _)| quotient)
+    }
+
+    /// Divides two scalar integer values of the same type, returning the remainder.
+    ///
+    /// Returns an error if `rhs` is zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `rhs` - The value to divide by.
+    pub fn checked_rem(&self, rhs: &Value) -> Result<Value, TypeError> {
+        self.div_rem(rhs).map(|(_, remainder)| remainder)
+    }
+
+    fn div_rem(&self, rhs: &Value) -> Result<(Value, Value), TypeError> {
+        if let Value::Bit(_) | Value::Array(_) | Value::Struct(_) | Value::Named { .. } = self {
+            return Err(TypeError::NotInteger {
+                actual: self.value_type(),
+            });
+        }
+
+        let (expected, actual) = (self.value_type(), rhs.value_type());
+        if expected != actual {
+            return Err(TypeError::UnexpectedType { expected, actual });
+        }
+
+        if rhs.is_zero() {
+            return Err(TypeError::DivisionByZero);
+        }
+
+        Ok(match (self, rhs) {
+            (Value::U8(a), Value::U8(b)) => (Value::U8(a / b), Value::U8(a % b)),
+            (Value::U16(a), Value::U16(b)) => (Value::U16(a / b), Value::U16(a % b)),
+            (Value::U32(a), Value::U32(b)) => (Value::U32(a / b), Value::U32(a % b)),
+            (Value::U64(a), Value::U64(b)) => (Value::U64(a / b), Value::U64(a % b)),
+            (Value::U128(a), Value::U128(b)) => (Value::U128(a / b), Value::U128(a % b)),
+            (Value::Uint(a), Value::Uint(b)) => {
+                // Restoring long division, with the remainder one bit wider than the
+                // operands so that shifting it never overflows.
+                let width = a.len();
+                let mut quotient = vec![false; width];
+                let mut remainder = vec![false; width + 1];
+                for i in (0..width).rev() {
+                    remainder.pop();
+                    remainder.insert(0, a[i]);
+
+                    let ge = (0..=width)
+                        .rev()
+                        .map(|j| (remainder[j], j < width && b[j]))
+                        .find(|(r, b)| r != b)
+                        .is_none_or(|(r, _)| r);
+                    if ge {
+                        let mut borrow = false;
+                        for (j, r) in remainder.iter_mut().enumerate() {
+                            let b = j < width && b[j];
+                            let diff = *r ^ b ^ borrow;
+                            borrow = (!*r & (b | borrow)) | (*r & b & borrow);
+                            *r = diff;
+                        }
+                        quotient[i] = true;
+                    }
+                }
+                remainder.truncate(width);
+                (Value::Uint(quotient), Value::Uint(remainder))
+            }
+            _ => unreachable!("types are checked to be equal integers"),
+        })
+    }
+
+    /// Returns a structured description of the differences between two values of the
+    /// same type.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The value to compare with.
+    pub fn diff(&self, other: &Value) -> Result<ValueDiff, TypeError> {
+        let (expected, actual) = (self.value_type(), other.value_type());
+        if expected != actual {
+            return Err(TypeError::UnexpectedType { expected, actual });
+        }
+
+        Ok(self.diff_inner(other))
+    }
+
+    fn diff_inner(&self, other: &Value) -> ValueDiff {
+        let diffs = |a: &[Value], b: &[Value]| -> Vec<(usize, ValueDiff)> {
+            a.iter()
+                .zip(b)
+                .map(|(a, b)| a.diff_inner(b))
+                .enumerate()
+                .filter(|(_, diff)| !diff.is_equal())
+                .collect()
+        };
+
+        match (self, other) {
+            _ if self == other => ValueDiff::Equal,
+            (Value::Array(a), Value::Array(b)) => ValueDiff::Array(diffs(a, b)),
+            (Value::Struct(a), Value::Struct(b)) => ValueDiff::Struct(diffs(a, b)),
+            (Value::Named { inner: a, .. }, Value::Named { inner: b, .. }) => a.diff_inner(b),
+            _ => ValueDiff::Scalar {
+                left: self.clone(),
+                right: other.clone(),
+            },
+        }
+    }
+
+    /// Returns the index of the first bit at which two values of the same type differ.
+    ///
+    /// The index is into the lsb0 bits of the whole value, as yielded by
+    /// [`IntoBits::into_iter_lsb0`], or `None` if the values are equal.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The value to compare with.
+    pub fn first_diff_bit(&self, other: &Value) -> Result<Option<usize>, TypeError> {
+        let (expected, actual) = (self.value_type(), other.value_type());
+        if expected != actual {
+            return Err(TypeError::UnexpectedType { expected, actual });
+        }
+
+        Ok(self
+            .clone()
+            .into_iter_lsb0()
+            .zip(other.clone().into_iter_lsb0())
+            .position(|(a, b)| a != b))
+    }
+
+    /// Returns the number of bits at which two values of the same type differ.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The value to compare with.
+    pub fn hamming_distance(&self, other: &Value) -> Result<u32, TypeError> {
+        let (expected, actual) = (self.value_type(), other.value_type());
+        if expected != actual {
+            return Err(TypeError::UnexpectedType { expected, actual });
+        }
+
+        Ok(self.hamming_distance_inner(other))
+    }
+
+    fn hamming_distance_inner(&self, other: &Value) -> u32 {
+        match (self, other) {
+            (Value::Bit(a), Value::Bit(b)) => (a ^ b) as u32,
+            (Value::U8(a), Value::U8(b)) => (a ^ b).count_ones(),
+            (Value::U16(a), Value::U16(b)) => (a ^ b).count_ones(),
+            (Value::U32(a), Value::U32(b)) => (a ^ b).count_ones(),
+            (Value::U64(a), Value::U64(b)) => (a ^ b).count_ones(),
+            (Value::U128(a), Value::U128(b)) => (a ^ b).count_ones(),
+            (Value::Uint(a), Value::Uint(b)) => {
+                a.iter().zip(b).filter(|(a, b)| a != b).count() as u32
+            }
+            (Value::Array(a), Value::Array(b)) | (Value::Struct(a), Value::Struct(b)) => a
+                .iter()
+                .zip(b)
+                .map(|(a, b)| a.hamming_distance_inner(b))
+                .sum(),
+            (Value::Named { inner: a, .. }, Value::Named { inner: b, .. }) => {
+                a.hamming_distance_inner(b)
+            }
+            _ => unreachable!("types should match"),
+        }
+    }
+
+    /// Reinterprets the bits of the value as a value of the target type.
+    ///
+    /// The value is flattened into its lsb0 bits, which are then decoded as the target
+    /// type. For example, a `U32` can be reinterpreted as `[U8; 4]`, in which case the
+    /// first element holds the least significant byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The type to reinterpret the value as, which must have the same bit length.
+    pub fn reinterpret(&self, target: &ValueType) -> Result<Value, TypeError> {
+        if self.bit_len() != target.len() {
+            return Err(TypeError::invalid_length(target.len(), self.bit_len()));
+        }
+
+        let bits: Vec<bool> = self.clone().into_iter_lsb0().collect();
+        Ok(Value::decode_lsb0(target, &bits))
+    }
+
+    /// Encodes the value into a self-describing byte format with a leading version byte.
+    ///
+    /// The encoding consists of [`VALUE_ENCODING_VERSION`], followed by the
+    /// [type code](ValueType::type_code) of the value and its lsb0 bits packed into bytes,
+    /// with the last byte padded with zeros. Unlike the serde representation, the encoding
+    /// can be rejected by [`Value::decode_versioned`] if it was written by an incompatible
+    /// version.
+    pub fn encode_versioned(&self) -> Vec<u8> {
+        let mut bytes = vec![VALUE_ENCODING_VERSION];
+        self.value_type().write_type_code(&mut bytes);
+        bytes.extend(
+            self.clone()
+                .into_iter_lsb0()
+                .collect::<Vec<_>>()
+                .chunks(8)
+                .map(|bits| u8::from_lsb0_iter(bits.iter().copied())),
+        );
+        bytes
+    }
+
+    /// Decodes a value from the encoding produced by [`Value::encode_versioned`].
+    ///
+    /// Returns an error if the version byte does not match [`VALUE_ENCODING_VERSION`],
+    /// rather than attempting to interpret the rest of the bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The encoded value.
+    pub fn decode_versioned(bytes: &[u8]) -> Result<Value, TypeError> {
+        let Some(&version) = bytes.first() else {
+            return Err(TypeError::invalid_length(1, 0));
+        };
+        if version != VALUE_ENCODING_VERSION {
+            return Err(TypeError::UnsupportedVersion { version });
+        }
+
+        let mut position = 1;
+        let ty = ValueType::read_type_code(bytes, &mut position, 0)?;
+        // The type is untrusted, so its length is checked before it is used.
+        let len = ty
+            .decodable_len()
+            .map_err(|reason| TypeError::InvalidTypeCode {
+                position: 1,
+                reason: reason.to_string(),
+            })?;
+
+        let rest = &bytes[position..];
+        if rest.len() != len.div_ceil(8) {
+            return Err(TypeError::invalid_length(len.div_ceil(8), rest.len()));
+        }
+
+        let bits: Vec<bool> = rest
+            .iter()
+            .flat_map(|byte| byte.into_iter_lsb0())
+            .take(len)
+            .collect();
+
+        Ok(Value::decode_lsb0(&ty, &bits))
+    }
+
+    /// Creates a value of the given type from an iterator of bits in the given order.
+    ///
+    /// The iterator must yield exactly `ty.len()` bits. It is consumed entirely so that
+    /// the actual number of bits can be reported on error.
+    ///
+    /// # Arguments
+    ///
+    /// * `ty` - The type of the value.
+    /// * `bits` - The bits of the value.
+    /// * `order` - The order of the bits within each scalar.
+    pub fn from_bits<I: IntoIterator<Item = bool>>(
+        ty: &ValueType,
+        bits: I,
+        order: BitOrder,
+    ) -> Result<Value, TypeError> {
+        let len = ty.len();
+        let mut bits = bits.into_iter();
+        let collected: Vec<bool> = bits.by_ref().take(len).collect();
+        let extra = bits.count();
+        if collected.len() != len || extra > 0 {
+            return Err(TypeError::invalid_length(len, collected.len() + extra));
+        }
+
+        let value = Value::decode_lsb0(ty, &collected);
+        match order {
+            BitOrder::Lsb0 => Ok(value),
+            BitOrder::Msb0 => value.reverse_bits(),
+        }
+    }
+
+    /// Creates an integer value from an array of `Bit` values in the given order.
+    ///
+    /// This is the inverse of [`Value::int_to_bits`].
+    ///
+    /// # Arguments
+    ///
+    /// * `array` - The array of bits, which must have the same length as the target type.
+    /// * `target` - The integer type to create.
+    /// * `order` - The order of the bits in the array.
+    pub fn bits_to_int(
+        array: &Value,
+        target: &ValueType,
+        order: BitOrder,
+    ) -> Result<Value, TypeError> {
+        if !target.is_integer() {
+            return Err(TypeError::NotInteger {
+                actual: target.clone(),
+            });
+        }
+
+        let bits = match array {
+            Value::Array(v) if v.len() == target.len() => v
+                .iter()
+                .map(|v| match v {
+                    Value::Bit(bit) => Ok(*bit),
+                    v => Err(TypeError::UnexpectedType {
+                        expected: ValueType::Bit,
+                        actual: v.value_type(),
+                    }),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            v => {
+                return Err(TypeError::UnexpectedType {
+                    expected: ValueType::Array(Box::new(ValueType::Bit), target.len()),
+                    actual: v.value_type(),
+                })
+            }
+        };
+
+        Value::from_bits(target, bits, order)
+    }
+
+    /// Converts an integer value into an array of `Bit` values in the given order.
+    ///
+    /// # Arguments
+    ///
+    /// * `order` - The order of the bits in the array.
+    pub fn int_to_bits(&self, order: BitOrder) -> Result<Value, TypeError> {
+        if !self.is_integer() {
+            return Err(TypeError::NotInteger {
+                actual: self.value_type(),
+            });
+        }
+
+        Ok(Value::Array(
+            self.clone()
+                .into_bits_vec(order)
+                .into_iter()
+                .map(Value::Bit)
+                .collect(),
+        ))
+    }
+
+    /// Interleaves the bits of scalar values into a bit-sliced array of `Bit`s.
+    ///
+    /// Bit `i` of every lane is grouped together, so element `i * lanes.len() + j` of the
+    /// returned array is bit `i` (lsb0) of lane `j`.
+    ///
+    /// # Arguments
+    ///
+    /// * `lanes` - The values to interleave, which must all be scalars of the same type.
+    pub fn interleave(lanes: &[Value]) -> Result<Value, TypeError> {
+        let Some(first) = lanes.first() else {
+            return Err(TypeError::ZeroLengthArray);
+        };
+
+        let ty = first.value_type();
+        if !ty.is_scalar() {
+            return Err(TypeError::UnexpectedType {
+                expected: ValueType::Bit,
+                actual: ty,
+            });
+        }
+
+        let mut lane_bits = Vec::with_capacity(lanes.len());
+        for lane in lanes {
+            if lane.value_type() != ty {
+                return Err(TypeError::UnexpectedType {
+                    expected: ty,
+                    actual: lane.value_type(),
+                });
+            }
+            lane_bits.push(lane.clone().into_iter_lsb0().collect::<Vec<_>>());
+        }
+
+        Ok(Value::Array(
+            (0..ty.len())
+                .flat_map(|i| lane_bits.iter().map(move |bits| Value::Bit(bits[i])))
+                .collect(),
+        ))
+    }
+
+    /// Splits a bit-sliced array of `Bit`s back into its lanes.
+    ///
+    /// This is the inverse of [`Value::interleave`].
+    ///
+    /// # Arguments
+    ///
+    /// * `lanes` - The number of lanes.
+    /// * `ty` - The scalar type of each lane.
+    pub fn deinterleave(&self, lanes: usize, ty: &ValueType) -> Result<Vec<Value>, TypeError> {
+        if lanes == 0 {
+            return Err(TypeError::ZeroLengthArray);
+        }
+
+        if !ty.is_scalar() {
+            return Err(TypeError::UnexpectedType {
+                expected: ValueType::Bit,
+                actual: ty.clone(),
+            });
+        }
+
+        let expected = ValueType::Array(Box::new(ValueType::Bit), lanes * ty.len());
+        let bits = match self {
+            Value::Array(v) if v.len() == lanes * ty.len() => v
+                .iter()
+                .map(|v| match v {
+                    Value::Bit(bit) => Ok(*bit),
+                    v => Err(TypeError::UnexpectedType {
+                        expected: ValueType::Bit,
+                        actual: v.value_type(),
+                    }),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            v => {
+                return Err(TypeError::UnexpectedType {
+                    expected,
+                    actual: v.value_type(),
+                })
+            }
+        };
+
+        Ok((0..lanes)
+            .map(|j| {
+                let lane_bits: Vec<bool> = bits.iter().skip(j).step_by(lanes).copied().collect();
+                Value::decode_lsb0(ty, &lane_bits)
+            })
+            .collect())
+    }
+
+    /// Extracts a range of bits from the value, decoding them as the target type.
+    ///
+    /// The range is into the lsb0 bits of the whole value, as yielded by
+    /// [`IntoBits::into_iter_lsb0`].
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first bit to extract.
+    /// * `len` - The number of bits to extract.
+    /// * `target` - The type to decode the bits as, which must have length `len`.
+    pub fn extract_bits(
+        &self,
+        start: usize,
+        len: usize,
+        target: &ValueType,
+    ) -> Result<Value, TypeError> {
+        if target.len() != len {
+            return Err(TypeError::invalid_length(len, target.len()));
+        }
+
+        let end = start.saturating_add(len);
+        if end > self.bit_len() {
+            return Err(TypeError::invalid_length(self.bit_len(), end));
+        }
+
+        let bits: Vec<bool> = self
+            .clone()
+            .into_iter_lsb0()
+            .skip(start)
+            .take(len)
+            .collect();
+        Ok(Value::decode_lsb0(target, &bits))
+    }
+
+    /// Decodes a value of the given type from its lsb0 bits.
+    ///
+    /// The number of bits must equal the length of the type.
+    fn decode_lsb0(ty: &ValueType, bits: &[bool]) -> Value {
+        debug_assert_eq!(bits.len(), ty.len());
+        let bits_iter = bits.iter().copied();
+        match ty {
+            ValueType::Bit => Value::Bit(bits[0]),
+            ValueType::U8 => Value::U8(u8::from_lsb0_iter(bits_iter)),
+            ValueType::U16 => Value::U16(u16::from_lsb0_iter(bits_iter)),
+            ValueType::U32 => Value::U32(u32::from_lsb0_iter(bits_iter)),
+            ValueType::U64 => Value::U64(u64::from_lsb0_iter(bits_iter)),
+            ValueType::U128 => Value::U128(u128::from_lsb0_iter(bits_iter)),
+            ValueType::Uint(_) => Value::Uint(bits.to_vec()),
+            // Elements of zero width have no bits to chunk, but there are still `len` of them.
+            ValueType::Array(elem, len) if elem.len() == 0 => {
+                Value::Array((0..*len).map(|_| Value::decode_lsb0(elem, &[])).collect())
+            }
+            ValueType::Array(elem, _) => Value::Array(
+                bits.chunks(elem.len())
+                    .map(|bits| Value::decode_lsb0(elem, bits))
+                    .collect(),
+            ),
+            ValueType::Struct(fields) => {
+                let mut offset = 0;
+                Value::Struct(
+                    fields
+                        .iter()
+                        .map(|ty| {
+                            let bits = &bits[offset..offset + ty.len()];
+                            offset += ty.len();
+                            Value::decode_lsb0(ty, bits)
+                        })
+                        .collect(),
+                )
+            }
+            ValueType::Named { name, inner } => Value::Named {
+                name: name.clone(),
+                inner: Box::new(Value::decode_lsb0(inner, bits)),
+            },
+        }
+    }
+
+    /// Returns the smaller of two integer values of the same type, comparing them as
+    /// unsigned integers.
+    ///
+    /// Arrays and structs are compared element-wise.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The value to compare with.
+    pub fn min(&self, other: &Value) -> Result<Value, TypeError> {
+        self.zip_map(other, |a, b| {
+            Ok(match a.cmp_integer(b)? {
+                Ordering::Greater => b.clone(),
+                _ => a.clone(),
+            })
+        })
+    }
+
+    /// Returns the larger of two integer values of the same type, comparing them as
+    /// unsigned integers.
+    ///
+    /// Arrays and structs are compared element-wise.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The value to compare with.
+    pub fn max(&self, other: &Value) -> Result<Value, TypeError> {
+        self.zip_map(other, |a, b| {
+            Ok(match a.cmp_integer(b)? {
+                Ordering::Less => b.clone(),
+                _ => a.clone(),
+            })
+        })
+    }
+
+    /// Clamps an integer value into the inclusive range `[min, max]`, comparing values as
+    /// unsigned integers.
+    ///
+    /// Arrays are clamped element-wise, with bounds which are either arrays of the same
+    /// length or scalars applied to every element. If `min` is greater than `max` the
+    /// result is `max`.
+    ///
+    /// # Arguments
+    ///
+    /// * `min` - The lower bound.
+    /// * `max` - The upper bound.
+    pub fn clamp(&self, min: &Value, max: &Value) -> Result<Value, TypeError> {
+        let Value::Array(v) = self else {
+            return self.max(min)?.min(max);
+        };
+
+        let bound = |bound: &Value, i: usize| -> Result<Value, TypeError> {
+            match bound {
+                Value::Array(b) if b.len() == v.len() => Ok(b[i].clone()),
+                Value::Array(_) => Err(TypeError::UnexpectedType {
+                    expected: self.value_type(),
+                    actual: bound.value_type(),
+                }),
+                b => Ok(b.clone()),
+            }
+        };
+
+        Ok(Value::Array(
+            v.iter()
+                .enumerate()
+                .map(|(i, v)| v.clamp(&bound(min, i)?, &bound(max, i)?))
+                .collect::<Result<Vec<_>, _>>()?,
+        ))
+    }
+
+    /// Returns a copy of an array value with its elements sorted in ascending order.
+    ///
+    /// Integers are compared as unsigned integers, and arrays and structs are compared
+    /// lexicographically by their elements. All elements must have the same type, and
+    /// every scalar in that type must be an integer.
+    pub fn sorted(&self) -> Result<Value, TypeError> {
+        let elems = self.as_elements()?;
+        let Some(first) = elems.first() else {
+            return Ok(self.clone());
+        };
+
+        let expected = first.value_type();
+        if let Some(elem) = elems.iter().find(|elem| elem.value_type() != expected) {
+            return Err(TypeError::UnexpectedType {
+                expected,
+                actual: elem.value_type(),
+            });
+        }
+        // Comparing the first element with itself checks that every scalar is an integer.
+        first.cmp_lexicographic(first)?;
+
+        let mut sorted = elems.to_vec();
+        sorted.sort_by(|a, b| {
+            a.cmp_lexicographic(b)
+                .expect("elements should have the same integer type")
+        });
+
+        Ok(Value::Array(sorted))
+    }
+
+    /// Compares two values of the same type lexicographically, comparing scalars as
+    /// unsigned integers.
+    fn cmp_lexicographic(&self, other: &Value) -> Result<Ordering, TypeError> {
+        match (self, other) {
+            (Value::Array(a), Value::Array(b)) | (Value::Struct(a), Value::Struct(b)) => {
+                for (a, b) in a.iter().zip(b) {
+                    match a.cmp_lexicographic(b)? {
+                        Ordering::Equal => continue,
+                        ordering => return Ok(ordering),
+                    }
+                }
+                Ok(a.len().cmp(&b.len()))
+            }
+            (Value::Named { inner: a, .. }, Value::Named { inner: b, .. }) => {
+                a.cmp_lexicographic(b)
+            }
+            (a, b) => a.cmp_integer(b),
+        }
+    }
+
+    /// Compares two scalar integer values of the same type.
+    fn cmp_integer(&self, other: &Value) -> Result<Ordering, TypeError> {
+        match (self, other) {
+            (Value::U8(a), Value::U8(b)) => Ok(a.cmp(b)),
+            (Value::U16(a), Value::U16(b)) => Ok(a.cmp(b)),
+            (Value::U32(a), Value::U32(b)) => Ok(a.cmp(b)),
+            (Value::U64(a), Value::U64(b)) => Ok(a.cmp(b)),
+            (Value::U128(a), Value::U128(b)) => Ok(a.cmp(b)),
+            // Bits are stored in lsb0 order, so compare from the most significant bit.
+            (Value::Uint(a), Value::Uint(b)) if a.len() == b.len() => {
+                Ok(a.iter().rev().cmp(b.iter().rev()))
+            }
+            (Value::Bit(_) | Value::Array(_) | Value::Struct(_) | Value::Named { .. }, _) => {
+                Err(TypeError::NotInteger {
+                    actual: self.value_type(),
+                })
+            }
+            _ => Err(TypeError::UnexpectedType {
+                expected: self.value_type(),
+                actual: other.value_type(),
+            }),
+        }
+    }
+
+    /// Reverses the order of the bits of the value.
+    ///
+    /// `Bit` values are returned unchanged, and arrays have the bits of each element
+    /// reversed while the order of the elements is preserved.
+    pub fn reverse_bits(&self) -> Result<Value, TypeError> {
+        self.map_leaves(|v| {
+            Ok(match v {
+                Value::Bit(v) => Value::Bit(*v),
+                Value::U8(v) => Value::U8(v.reverse_bits()),
+                Value::U16(v) => Value::U16(v.reverse_bits()),
+                Value::U32(v) => Value::U32(v.reverse_bits()),
+                Value::U64(v) => Value::U64(v.reverse_bits()),
+                Value::U128(v) => Value::U128(v.reverse_bits()),
+                Value::Uint(v) => Value::Uint(v.iter().rev().copied().collect()),
+                Value::Array(_) | Value::Struct(_) | Value::Named { .. } => {
+                    unreachable!("leaves should not be aggregates")
+                }
+            })
+        })
+    }
+
+    /// Applies `f` to each scalar leaf of the value, preserving its shape.
+    fn map_leaves<F>(&self, mut f: F) -> Result<Value, TypeError>
+    where
+        F: FnMut(&Value) -> Result<Value, TypeError>,
+    {
+        self.map_leaves_inner(&mut f)
+    }
+
+    fn map_leaves_inner<F>(&self, f: &mut F) -> Result<Value, TypeError>
+    where
+        F: FnMut(&Value) -> Result<Value, TypeError>,
+    {
+        match self {
+            Value::Array(v) => Ok(Value::Array(
+                v.iter()
+                    .map(|v| v.map_leaves_inner(f))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            Value::Struct(v) => Ok(Value::Struct(
+                v.iter()
+                    .map(|v| v.map_leaves_inner(f))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            Value::Named { name, inner } => Ok(Value::Named {
+                name: name.clone(),
+                inner: Box::new(inner.map_leaves_inner(f)?),
+            }),
+            v => f(v),
+        }
+    }
+
+    /// Returns the value in compact notation, collapsing runs of equal array elements.
+    ///
+    /// See [`CompactValue`] for a description of the notation.
+    pub fn to_string_compact(&self) -> String {
+        let mut s = String::new();
+        write_compact(self, &mut s).expect("writing to a string should not fail");
+        s
+    }
+
+    /// Parses a value from compact notation, as produced by [`Value::to_string_compact`].
+    pub fn from_str_compact(s: &str) -> Result<Value, TypeError> {
+        let mut parser = CompactParser {
+            s,
+            pos: 0,
+            depth: 0,
+            elements: 0,
+        };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if !parser.rest().is_empty() {
+            return Err(parser.error("unexpected trailing input"));
+        }
+        Ok(value)
+    }
+
+    /// Returns the value formatted according to the given options.
+    ///
+    /// Arrays are written as `[a, b, ...]`, structs as `{a, b, ...}` and scalars as they
+    /// are displayed, with integers written in the configured radix. Arrays with more
+    /// elements than [`DisplayOpts::max_elements`] show only their first and last
+    /// elements, separated by `…`, which keeps the output bounded for large arrays.
+    ///
+    /// # Arguments
+    ///
+    /// * `opts` - The formatting options.
+    pub fn display_with(&self, opts: DisplayOpts) -> String {
+        let mut s = String::new();
+        write_display_with(self, &opts, &mut s).expect("writing to a string should not fail");
+        s
+    }
+
+    /// Parses a value of the given type from a string.
+    ///
+    /// Integers may be given in decimal, or in hex or binary with a `0x` or `0b` prefix.
+    /// Decimal input is limited to 128 bits, even for wider `Uint` types. Bits are given
+    /// as `0`, `1`, `true` or `false`. Arrays are given as bracketed, comma-separated
+    /// lists, eg. `[1, 2, 3]`, and structs as lists of fields in braces, eg. `{1, [2, 3]}`.
+    ///
+    /// Values which overflow the width of the type are rejected. All integer types are
+    /// unsigned, so negative values are rejected as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `ty` - The type of the value.
+    /// * `s` - The string to parse.
+    pub fn from_str(ty: &ValueType, s: &str) -> Result<Value, TypeError> {
+        Value::parse_typed(ty, s, 0)
+    }
+
+    /// Parses a value of the given type, where `offset` is the position of `s` in the
+    /// input for error reporting.
+    fn parse_typed(ty: &ValueType, s: &str, offset: usize) -> Result<Value, TypeError> {
+        let trimmed = s.trim_start();
+        let position = offset + s.len() - trimmed.len();
+        let s = trimmed.trim_end();
+        let error = |reason: String| TypeError::InvalidSyntax { position, reason };
+
+        match ty {
+            ValueType::Array(elem, len) => {
+                let elems = split_list(s, '[', ']')
+                    .ok_or_else(|| error(format!("expected an array of {} elements", len)))?;
+                if elems.len() != *len {
+                    return Err(error(format!(
+                        "expected {} array elements, got {}",
+                        len,
+                        elems.len()
+                    )));
+                }
+                Ok(Value::Array(
+                    elems
+                        .into_iter()
+                        .map(|(pos, s)| Value::parse_typed(elem, s, position + pos))
+                        .collect::<Result<Vec<_>, _>>()?,
+                ))
+            }
+            ValueType::Struct(fields) => {
+                let elems = split_list(s, '{', '}').ok_or_else(|| {
+                    error(format!("expected a struct of {} fields", fields.len()))
+                })?;
+                if elems.len() != fields.len() {
+                    return Err(error(format!(
+                        "expected {} struct fields, got {}",
+                        fields.len(),
+                        elems.len()
+                    )));
+                }
+                Ok(Value::Struct(
+                    fields
+                        .iter()
+                        .zip(elems)
+                        .map(|(ty, (pos, s))| Value::parse_typed(ty, s, position + pos))
+                        .collect::<Result<Vec<_>, _>>()?,
+                ))
+            }
+            ValueType::Named { name, inner } => Ok(Value::Named {
+                name: name.clone(),
+                inner: Box::new(Value::parse_typed(inner, s, position)?),
+            }),
+            ValueType::Bit => match s {
+                "0" | "false" => Ok(Value::Bit(false)),
+                "1" | "true" => Ok(Value::Bit(true)),
+                s => Err(error(format!("invalid bit: {:?}", s))),
+            },
+            ty => {
+                let bits = parse_unsigned(s, ty.len())
+                    .map_err(|reason| error(format!("invalid {}: {}", ty, reason)))?;
+                Ok(Value::decode_lsb0(ty, &bits))
+            }
+        }
+    }
+
+    /// Folds `f` over the scalar leaves of the value, from left to right.
+    ///
+    /// Leaves are visited in the same order as their bits are yielded by
+    /// [`IntoBits::into_iter_lsb0`].
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - The initial accumulator.
+    /// * `f` - The function to apply to the accumulator and each leaf.
+    pub fn try_fold<B, F>(&self, init: B, mut f: F) -> Result<B, TypeError>
+    where
+        F: FnMut(B, &Value) -> Result<B, TypeError>,
+    {
+        self.try_fold_inner(init, &mut f)
+    }
+
+    fn try_fold_inner<B, F>(&self, init: B, f: &mut F) -> Result<B, TypeError>
+    where
+        F: FnMut(B, &Value) -> Result<B, TypeError>,
+    {
+        match self {
+            Value::Array(v) | Value::Struct(v) => {
+                v.iter().try_fold(init, |acc, v| v.try_fold_inner(acc, f))
+            }
+            Value::Named { inner, .. } => inner.try_fold_inner(init, f),
+            v => f(init, v),
+        }
+    }
+
+    /// Returns the scalar leaf at the given position, or `None` if the index is out of
+    /// bounds.
+    ///
+    /// Leaves are indexed in the same order as they are visited by [`Value::try_fold`].
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the leaf.
+    pub fn get_flat(&self, mut index: usize) -> Option<&Value> {
+        self.get_flat_inner(&mut index)
+    }
+
+    fn get_flat_inner(&self, index: &mut usize) -> Option<&Value> {
+        match self {
+            Value::Array(v) | Value::Struct(v) => v.iter().find_map(|v| v.get_flat_inner(index)),
+            Value::Named { inner, .. } => inner.get_flat_inner(index),
+            v if *index == 0 => Some(v),
+            _ => {
+                *index -= 1;
+                None
+            }
+        }
+    }
+
+    /// Returns a mutable reference to the scalar leaf at the given position, or `None`
+    /// if the index is out of bounds.
+    ///
+    /// See [`Value::get_flat`].
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the leaf.
+    pub fn get_flat_mut(&mut self, mut index: usize) -> Option<&mut Value> {
+        self.get_flat_mut_inner(&mut index)
+    }
+
+    fn get_flat_mut_inner(&mut self, index: &mut usize) -> Option<&mut Value> {
+        match self {
+            Value::Array(v) | Value::Struct(v) => {
+                v.iter_mut().find_map(|v| v.get_flat_mut_inner(index))
+            }
+            Value::Named { inner, .. } => inner.get_flat_mut_inner(index),
+            v if *index == 0 => Some(v),
+            _ => {
+                *index -= 1;
+                None
+            }
+        }
+    }
+
+    /// Casts an integer value to the target integer type, saturating at the
+    /// maximum value of the target type instead of truncating.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The integer type to cast to.
+    ///
+    /// # Returns
+    ///
+    /// The cast value, or an error if either the value or the target is not an integer type,
+    /// or if the value is a `Uint` wider than 128 bits.
+    pub fn saturating_cast(&self, target: &ValueType) -> Result<Value, TypeError> {
+        let value = self.to_u128()?;
+
+        let max = match target {
+            ValueType::U8 => u8::MAX as u128,
+            ValueType::U16 => u16::MAX as u128,
+            ValueType::U32 => u32::MAX as u128,
+            ValueType::U64 => u64::MAX as u128,
+            ValueType::U128 => u128::MAX,
+            ValueType::Uint(width) if *width >= 128 => u128::MAX,
+            ValueType::Uint(width) => (1 << width) - 1,
+            _ => {
+                return Err(TypeError::NotInteger {
+                    actual: target.clone(),
+                })
+            }
+        };
+
+        Ok(Value::from_u128(target, value.min(max)).expect("target should be an integer type"))
+    }
+
+    /// Creates an integer value of the given type with the low `k` bits set.
+    ///
+    /// # Arguments
+    ///
+    /// * `ty` - The integer type of the mask.
+    /// * `k` - The number of bits to set, which must be at most the width of the type.
+    pub fn low_mask(ty: &ValueType, k: usize) -> Result<Value, TypeError> {
+        Value::check_mask(ty, k)?;
+        let bits: Vec<bool> = (0..ty.len()).map(|i| i < k).collect();
+        Ok(Value::decode_lsb0(ty, &bits))
+    }
+
+    /// Creates an integer value of the given type with the high `k` bits set.
+    ///
+    /// # Arguments
+    ///
+    /// * `ty` - The integer type of the mask.
+    /// * `k` - The number of bits to set, which must be at most the width of the type.
+    pub fn high_mask(ty: &ValueType, k: usize) -> Result<Value, TypeError> {
+        Value::check_mask(ty, k)?;
+        let bits: Vec<bool> = (0..ty.len()).map(|i| i >= ty.len() - k).collect();
+        Ok(Value::decode_lsb0(ty, &bits))
+    }
+
+    /// Checks that a mask of `k` bits can be created for the given type.
+    fn check_mask(ty: &ValueType, k: usize) -> Result<(), TypeError> {
+        if !ty.is_integer() {
+            return Err(TypeError::NotInteger { actual: ty.clone() });
+        }
+
+        if k > ty.len() {
+            return Err(TypeError::invalid_length(ty.len(), k));
+        }
+
+        Ok(())
+    }
+
+    /// Returns an integer value as an array of `U8` values in big endian order.
+    ///
+    /// `Uint` values must have a width which is a multiple of 8.
+    pub fn to_byte_array(&self) -> Result<Value, TypeError> {
+        let bytes = self.to_be_bytes_vec()?;
+        Ok(Value::Array(bytes.into_iter().map(Value::U8).collect()))
+    }
+
+    /// Returns an integer value as an array of `U8` values in little endian order.
+    ///
+    /// `Uint` values must have a width which is a multiple of 8.
+    pub fn to_le_byte_array(&self) -> Result<Value, TypeError> {
+        let mut bytes = self.to_be_bytes_vec()?;
+        bytes.reverse();
+        Ok(Value::Array(bytes.into_iter().map(Value::U8).collect()))
+    }
+
+    /// Converts an integer value into an array of `U8` values in big endian order, see
+    /// [`Value::to_byte_array`].
+    pub fn into_byte_array(self) -> Result<Value, TypeError> {
+        self.to_byte_array()
+    }
+
+    /// Converts an integer value into an array of `U8` values in little endian order, see
+    /// [`Value::to_le_byte_array`].
+    pub fn into_le_byte_array(self) -> Result<Value, TypeError> {
+        self.to_le_byte_array()
+    }
+
+    /// Reassembles an integer value of the target type from an array of `U8` values in
+    /// big endian order.
+    ///
+    /// This is the inverse of [`Value::into_byte_array`].
+    ///
+    /// # Arguments
+    ///
+    /// * `array` - The array of bytes.
+    /// * `target` - The integer type to reassemble.
+    pub fn from_byte_array(array: &Value, target: &ValueType) -> Result<Value, TypeError> {
+        if !target.is_integer() {
+            return Err(TypeError::NotInteger {
+                actual: target.clone(),
+            });
+        }
+
+        let width = target.len();
+
+        if !width.is_multiple_of(8) {
+            return Err(TypeError::invalid_length(width.next_multiple_of(8), width));
+        }
+
+        let expected_type = ValueType::Array(Box::new(ValueType::U8), width / 8);
+        let bytes = match array {
+            Value::Array(v) if v.len() == width / 8 => v
+                .iter()
+                .map(|v| match v {
+                    Value::U8(v) => Ok(*v),
+                    v => Err(TypeError::UnexpectedType {
+                        expected: ValueType::U8,
+                        actual: v.value_type(),
+                    }),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            v => {
+                return Err(TypeError::UnexpectedType {
+                    expected: expected_type,
+                    actual: v.value_type(),
+                })
+            }
+        };
+
+        match target {
+            ValueType::Uint(width) => {
+                Value::uint_from_le_bytes(*width, &bytes.into_iter().rev().collect::<Vec<_>>())
+            }
+            _ => {
+                let value = bytes
+                    .into_iter()
+                    .fold(0u128, |acc, byte| (acc << 8) | byte as u128);
+                Ok(Value::from_u128(target, value).expect("target should be an integer type"))
+            }
+        }
+    }
+
+    /// Splits an integer value into an array of smaller integers in big endian order.
+    ///
+    /// This generalizes [`Value::to_byte_array`] to any integer chunk type, eg. a `U128`
+    /// segmented into `U32` values gives an array of 4 `U32` values, the first of which
+    /// holds the most significant bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `chunk` - The integer type of the segments, whose width must divide the width of
+    ///   the value.
+    pub fn segment(&self, chunk: &ValueType) -> Result<Value, TypeError> {
+        if !self.is_integer() {
+            return Err(TypeError::NotInteger {
+                actual: self.value_type(),
+            });
+        }
+
+        if !chunk.is_integer() || chunk.len() == 0 {
+            return Err(TypeError::NotInteger {
+                actual: chunk.clone(),
+            });
+        }
+
+        let (width, chunk_width) = (self.bit_len(), chunk.len());
+        if !width.is_multiple_of(chunk_width) {
+            return Err(TypeError::invalid_length(
+                width.next_multiple_of(chunk_width),
+                width,
+            ));
+        }
+
+        self.clone()
+            .into_bits_vec(BitOrder::Msb0)
+            .chunks(chunk_width)
+            .map(|bits| Value::from_bits(chunk, bits.iter().copied(), BitOrder::Msb0))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array)
+    }
+
+    /// Concatenates an array of integer values in big endian order into an integer of the
+    /// target type.
+    ///
+    /// This is the inverse of [`Value::segment`].
+    ///
+    /// # Arguments
+    ///
+    /// * `array` - The array of integers, whose total width must equal the width of the
+    ///   target type.
+    /// * `target` - The integer type to create.
+    pub fn join_segments(array: &Value, target: &ValueType) -> Result<Value, TypeError> {
+        if !target.is_integer() {
+            return Err(TypeError::NotInteger {
+                actual: target.clone(),
+            });
+        }
+
+        let mut bits = Vec::with_capacity(target.len());
+        for (index, elem) in array.as_elements()?.iter().enumerate() {
+            if !elem.is_integer() {
+                return Err(TypeError::ArrayElement {
+                    index,
+                    source: Box::new(TypeError::NotInteger {
+                        actual: elem.value_type(),
+                    }),
+                });
+            }
+
+            bits.extend(elem.clone().into_iter_msb0());
+        }
+
+        Value::from_bits(target, bits, BitOrder::Msb0)
+    }
+
+    /// Serializes an integer value as a big endian field element of `field_bytes` bytes,
+    /// left-padding with zeros.
+    ///
+    /// Returns an error if the width of the value exceeds the size of the field. `Uint`
+    /// values of any width are supported, padding the most significant byte with zeros.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_bytes` - The size of a field element in bytes.
+    pub fn to_field_bytes(&self, field_bytes: usize) -> Result<Vec<u8>, TypeError> {
+        let bytes = match self {
+            Value::Uint(v) => {
+                let mut bytes = Value::uint_bits_to_le_bytes(v);
+                bytes.reverse();
+                bytes
+            }
+            v => v.to_be_bytes_vec()?,
+        };
+
+        if bytes.len() > field_bytes {
+            return Err(TypeError::invalid_length(field_bytes, bytes.len()));
+        }
+
+        let mut padded = vec![0u8; field_bytes - bytes.len()];
+        padded.extend(bytes);
+
+        Ok(padded)
+    }
+
+    /// Returns the big endian bytes of an integer value.
+    fn to_be_bytes_vec(&self) -> Result<Vec<u8>, TypeError> {
+        Ok(match self {
+            Value::U8(v) => v.to_be_bytes().to_vec(),
+            Value::U16(v) => v.to_be_bytes().to_vec(),
+            Value::U32(v) => v.to_be_bytes().to_vec(),
+            Value::U64(v) => v.to_be_bytes().to_vec(),
+            Value::U128(v) => v.to_be_bytes().to_vec(),
+            Value::Uint(v) if v.len() % 8 == 0 => {
+                let mut bytes = Value::uint_bits_to_le_bytes(v);
+                bytes.reverse();
+                bytes
+            }
+            Value::Uint(v) => {
+                return Err(TypeError::invalid_length(
+                    v.len().next_multiple_of(8),
+                    v.len(),
+                ))
+            }
+            v => {
+                return Err(TypeError::NotInteger {
+                    actual: v.value_type(),
+                })
+            }
+        })
+    }
+
+    /// Promotes two integer values to a common type by zero-extending the narrower value
+    /// to the type of the wider value.
+    ///
+    /// Values of the same type are returned unchanged. It is an error for either value not
+    /// to be an integer, or for the values to have different types of the same width, eg.
+    /// `U8` and `Uint(8)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first value.
+    /// * `b` - The second value.
+    pub fn promote(a: &Value, b: &Value) -> Result<(Value, Value), TypeError> {
+        for v in [a, b] {
+            if !v.is_integer() {
+                return Err(TypeError::NotInteger {
+                    actual: v.value_type(),
+                });
+            }
+        }
+
+        let (a_ty, b_ty) = (a.value_type(), b.value_type());
+        let zero_extend = |v: &Value, ty: &ValueType| {
+            let bits: Vec<bool> = v
+                .clone()
+                .into_iter_lsb0()
+                .chain(core::iter::repeat(false))
+                .take(ty.len())
+                .collect();
+            Value::decode_lsb0(ty, &bits)
+        };
+
+        match a_ty.len().cmp(&b_ty.len()) {
+            Ordering::Less => Ok((zero_extend(a, &b_ty), b.clone())),
+            Ordering::Greater => Ok((a.clone(), zero_extend(b, &a_ty))),
+            Ordering::Equal if a_ty == b_ty => Ok((a.clone(), b.clone())),
+            Ordering::Equal => Err(TypeError::UnexpectedType {
+                expected: a_ty,
+                actual: b_ty,
+            }),
+        }
+    }
+
+    /// Returns the value of an integer variant widened to a `u128`.
+    fn to_u128(&self) -> Result<u128, TypeError> {
+        match self {
+            Value::U8(v) => Ok(*v as u128),
+            Value::U16(v) => Ok(*v as u128),
+            Value::U32(v) => Ok(*v as u128),
+            Value::U64(v) => Ok(*v as u128),
+            Value::U128(v) => Ok(*v),
+            Value::Uint(v) if v.len() <= 128 => Ok(u128::from_lsb0_iter(v.iter().copied())),
+            Value::Uint(v) => Err(TypeError::invalid_length(128, v.len())),
+            v => Err(TypeError::NotInteger {
+                actual: v.value_type(),
+            }),
+        }
+    }
+
+    /// Creates an integer value of the given type from a `u128`, truncating to its width.
+    fn from_u128(ty: &ValueType, value: u128) -> Option<Value> {
+        match ty {
+            ValueType::U8 => Some(Value::U8(value as u8)),
+            ValueType::U16 => Some(Value::U16(value as u16)),
+            ValueType::U32 => Some(Value::U32(value as u32)),
+            ValueType::U64 => Some(Value::U64(value as u64)),
+            ValueType::U128 => Some(Value::U128(value)),
+            ValueType::Uint(width) => Some(Value::Uint(
+                (0..*width)
+                    .map(|i| i < 128 && (value >> i) & 1 == 1)
+                    .collect(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl IntoBits for Value {
+    type IterLsb0 = alloc::vec::IntoIter<bool>;
+    type IterMsb0 = alloc::vec::IntoIter<bool>;
+
+    fn into_iter_lsb0(self) -> Self::IterLsb0 {
+        match self {
+            Value::Bit(v) => v.into_lsb0_vec(),
+            Value::U8(v) => v.into_lsb0_vec(),
+            Value::U16(v) => v.into_lsb0_vec(),
+            Value::U32(v) => v.into_lsb0_vec(),
+            Value::U64(v) => v.into_lsb0_vec(),
+            Value::U128(v) => v.into_lsb0_vec(),
+            Value::Uint(v) => v,
+            Value::Array(v) | Value::Struct(v) => {
+                v.into_iter().flat_map(|v| v.into_iter_lsb0()).collect()
+            }
+            Value::Named { inner, .. } => inner.into_iter_lsb0().collect(),
+        }
+        .into_iter()
+    }
+
+    fn into_iter_msb0(self) -> Self::IterMsb0 {
+        match self {
+            Value::Bit(v) => v.into_msb0_vec(),
+            Value::U8(v) => v.into_msb0_vec(),
+            Value::U16(v) => v.into_msb0_vec(),
+            Value::U32(v) => v.into_msb0_vec(),
+            Value::U64(v) => v.into_msb0_vec(),
+            Value::U128(v) => v.into_msb0_vec(),
+            Value::Uint(mut v) => {
+                v.reverse();
+                v
+            }
+            Value::Array(v) | Value::Struct(v) => {
+                v.into_iter().flat_map(|v| v.into_iter_msb0()).collect()
+            }
+            Value::Named { inner, .. } => inner.into_iter_msb0().collect(),
+        }
+        .into_iter()
+    }
+}
+
+/// Iterates over the elements of an array value or the fields of a struct value, or
+/// yields the value itself if it is a scalar.
+impl IntoIterator for Value {
+    type Item = Value;
+    type IntoIter = alloc::vec::IntoIter<Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Value::Array(v) | Value::Struct(v) => v.into_iter(),
+            v => vec![v].into_iter(),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bit(v) => write!(f, "Bit({})", v),
+            Value::U8(v) => write!(f, "U8({})", v),
+            Value::U16(v) => write!(f, "U16({})", v),
+            Value::U32(v) => write!(f, "U32({})", v),
+            Value::U64(v) => write!(f, "U64({})", v),
+            Value::U128(v) => write!(f, "U128({})", v),
+            Value::Uint(v) => {
+                write!(f, "Uint<{}>(0x", v.len())?;
+                for byte in Value::uint_bits_to_le_bytes(v).iter().rev() {
+                    write!(f, "{:02x}", byte)?;
+                }
+                write!(f, ")")
+            }
+            Value::Array(v) => write!(f, "Array({:?})", v),
+            Value::Struct(v) => write!(f, "Struct({:?})", v),
+            Value::Named { name, inner } => write!(f, "Named<{}>({})", name, inner),
+        }
+    }
+}
+
+/// A structured description of the differences between two values, see [`Value::diff`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ValueDiff {
+    /// The values are equal.
+    Equal,
+    /// Two scalar values differ.
+    Scalar {
+        /// The left value.
+        left: Value,
+        /// The right value.
+        right: Value,
+    },
+    /// The elements of two arrays differ, listed with their indices.
+    Array(Vec<(usize, ValueDiff)>),
+    /// The fields of two structs differ, listed with their indices.
+    Struct(Vec<(usize, ValueDiff)>),
+}
+
+impl ValueDiff {
+    /// Returns whether the values are equal.
+    pub fn is_equal(&self) -> bool {
+        matches!(self, ValueDiff::Equal)
+    }
+
+    fn fmt_at(&self, f: &mut Formatter<'_>, path: &mut String) -> fmt::Result {
+        match self {
+            ValueDiff::Equal => Ok(()),
+            ValueDiff::Scalar { left, right } => {
+                let path = if path.is_empty() { "value" } else { path };
+                writeln!(f, "{}: {} != {}", path, left, right)
+            }
+            ValueDiff::Array(diffs) | ValueDiff::Struct(diffs) => {
+                for (i, diff) in diffs {
+                    let len = path.len();
+                    match self {
+                        ValueDiff::Array(_) => path.push_str(&format!("[{}]", i)),
+                        _ => path.push_str(&format!(".{}", i)),
+                    }
+                    diff.fmt_at(f, path)?;
+                    path.truncate(len);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Displays one line per differing scalar, prefixed with its path, eg. `[1].0: U8(1) != U8(2)`.
+impl Display for ValueDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_equal() {
+            return writeln!(f, "values are equal");
+        }
+
+        self.fmt_at(f, &mut String::new())
+    }
+}
+
+/// A value which is displayed and serialized in a compact notation.
+///
+/// Scalars are written as they are displayed, arrays as `[a, b, ...]` with runs of equal
+/// elements collapsed into `a×count`, and structs as `{a, b, ...}`. For example, an array
+/// of 1024 zero bytes is written as `[U8(0)×1024]`. Named values are written as
+/// `Named<name>(value)`, with the name quoted as `"my-label"` unless it is made up of
+/// ASCII alphanumerics and underscores.
+///
+/// This is intended for human-readable diagnostics, see [`Value::to_string_compact`].
+/// When parsing, runs are expanded into at most [`MAX_COMPACT_ELEMENTS`] values in total,
+/// nested at most [`MAX_TYPE_DEPTH`] levels deep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactValue(pub Value);
+
+/// The maximum number of values, counting the elements of arrays and the fields of
+/// structs, that parsing compact notation may produce.
+pub const MAX_COMPACT_ELEMENTS: usize = 1 << 20;
+
+impl Display for CompactValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_compact(&self.0, f)
+    }
+}
+
+impl core::str::FromStr for CompactValue {
+    type Err = TypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Value::from_str_compact(s).map(CompactValue)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CompactValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CompactValue {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <String as serde::Deserialize>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+fn write_compact(value: &Value, f: &mut impl fmt::Write) -> fmt::Result {
+    match value {
+        Value::Array(v) => {
+            write!(f, "[")?;
+            let mut i = 0;
+            while i < v.len() {
+                let run = v[i..].iter().take_while(|elem| **elem == v[i]).count();
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_compact(&v[i], f)?;
+                if run > 1 {
+                    write!(f, "×{}", run)?;
+                }
+                i += run;
+            }
+            write!(f, "]")
+        }
+        Value::Struct(v) => {
+            write!(f, "{{")?;
+            for (i, field) in v.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_compact(field, f)?;
+            }
+            write!(f, "}}")
+        }
+        Value::Named { name, inner } => {
+            write!(f, "Named<")?;
+            write_compact_name(name, f)?;
+            write!(f, ">(")?;
+            write_compact(inner, f)?;
+            write!(f, ")")
+        }
+        v => write!(f, "{}", v),
+    }
+}
+
+/// Writes the name of a [`Value::Named`] in compact notation.
+///
+/// Names made up of ASCII alphanumerics and underscores are written as they are, any
+/// other name is quoted, escaping `"` and `\` with a backslash.
+fn write_compact_name(name: &str, f: &mut impl fmt::Write) -> fmt::Result {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return write!(f, "{}", name);
+    }
+
+    write!(f, "\"")?;
+    for c in name.chars() {
+        if c == '"' || c == '\\' {
+            write!(f, "\\")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    write!(f, "\"")
+}
+
+/// The radix in which integers are written by [`Value::display_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Radix {
+    /// Base 10. `Uint` values are always written in hex, as with [`Display`].
+    Decimal,
+    /// Base 16, with a `0x` prefix.
+    Hex,
+    /// Base 2, with a `0b` prefix.
+    Binary,
+}
+
+/// Options for formatting a value with [`Value::display_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisplayOpts {
+    /// The maximum number of elements shown for each array, or `None` to show all of
+    /// them. Longer arrays show the first and last elements up to this count in total.
+    pub max_elements: Option<usize>,
+    /// The radix integers are written in.
+    pub radix: Radix,
+    /// The separator written between array elements and struct fields.
+    pub separator: String,
+}
+
+impl Default for DisplayOpts {
+    fn default() -> Self {
+        Self {
+            max_elements: None,
+            radix: Radix::Decimal,
+            separator: ", ".to_string(),
+        }
+    }
+}
+
+fn write_display_with(value: &Value, opts: &DisplayOpts, f: &mut impl fmt::Write) -> fmt::Result {
+    macro_rules! int {
+        ($name:literal, $v:expr) => {
+            match opts.radix {
+                Radix::Decimal => write!(f, concat!($name, "({})"), $v),
+                Radix::Hex => write!(f, concat!($name, "({:#x})"), $v),
+                Radix::Binary => write!(f, concat!($name, "({:#b})"), $v),
+            }
+        };
+    }
+
+    match value {
+        Value::U8(v) => int!("U8", v),
+        Value::U16(v) => int!("U16", v),
+        Value::U32(v) => int!("U32", v),
+        Value::U64(v) => int!("U64", v),
+        Value::U128(v) => int!("U128", v),
+        Value::Uint(v) if opts.radix == Radix::Binary => {
+            write!(f, "Uint<{}>(0b", v.len())?;
+            for bit in v.iter().rev() {
+                write!(f, "{}", *bit as u8)?;
+            }
+            write!(f, ")")
+        }
+        Value::Array(v) => {
+            let (head, tail) = match opts.max_elements {
+                Some(max) if v.len() > max => (max.div_ceil(2), max / 2),
+                _ => (v.len(), 0),
+            };
+            write!(f, "[")?;
+            for (i, elem) in v[..head].iter().enumerate() {
+                if i > 0 {
+                    write!(f, "{}", opts.separator)?;
+                }
+                write_display_with(elem, opts, f)?;
+            }
+            if head < v.len() {
+                if head > 0 {
+                    write!(f, "{}", opts.separator)?;
+                }
+                write!(f, "…")?;
+                for elem in &v[v.len() - tail..] {
+                    write!(f, "{}", opts.separator)?;
+                    write_display_with(elem, opts, f)?;
+                }
+            }
+            write!(f, "]")
+        }
+        Value::Struct(v) => {
+            write!(f, "{{")?;
+            for (i, field) in v.iter().enumerate() {
+                if i > 0 {
+                    write!(f, "{}", opts.separator)?;
+                }
+                write_display_with(field, opts, f)?;
+            }
+            write!(f, "}}")
+        }
+        Value::Named { name, inner } => {
+            write!(f, "Named<{}>(", name)?;
+            write_display_with(inner, opts, f)?;
+            write!(f, ")")
+        }
+        v => write!(f, "{}", v),
+    }
+}
+
+/// Decodes the big endian hex digits of a `Uint` of the given width into little endian
+/// bytes, as written by the `Display` implementation of [`Value`].
+fn hex_to_le_bytes(width: usize, digits: &str) -> Option<Vec<u8>> {
+    if digits.len() != 2 * width.div_ceil(8) || !digits.is_ascii() {
+        return None;
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .rev()
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Splits a delimited, comma-separated list into its items, returning each item along
+/// with its position in `s`.
+///
+/// Commas nested within brackets or braces are not treated as separators.
+fn split_list(s: &str, open: char, close: char) -> Option<Vec<(usize, &str)>> {
+    let inner = s.strip_prefix(open)?.strip_suffix(close)?;
+    if inner.trim().is_empty() {
+        return Some(Vec::new());
+    }
+
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth = depth.checked_sub(1)?,
+            ',' if depth == 0 => {
+                items.push((open.len_utf8() + start, &inner[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push((open.len_utf8() + start, &inner[start..]));
+
+    Some(items)
+}
+
+/// Parses an unsigned integer into its lsb0 bits, padded to the given width.
+fn parse_unsigned(s: &str, width: usize) -> Result<Vec<bool>, String> {
+    if s.starts_with('-') {
+        return Err("negative values are not supported by unsigned types".to_string());
+    }
+
+    let (digits, bits_per_digit) = if let Some(digits) = s.strip_prefix("0x") {
+        (digits, 4)
+    } else if let Some(digits) = s.strip_prefix("0b") {
+        (digits, 1)
+    } else {
+        let value: u128 = s.parse().map_err(|e| format!("{}", e))?;
+        if width < 128 && value >> width != 0 {
+            return Err(format!("{} overflows {} bits", s, width));
+        }
+        return Ok((0..width)
+            .map(|i| i < 128 && (value >> i) & 1 == 1)
+            .collect());
+    };
+
+    if digits.is_empty() {
+        return Err("missing digits".to_string());
+    }
+
+    let mut bits = Vec::with_capacity(digits.len() * bits_per_digit);
+    for c in digits.chars().rev() {
+        let digit = c
+            .to_digit(1 << bits_per_digit)
+            .ok_or_else(|| format!("invalid digit: {:?}", c))?;
+        bits.extend((0..bits_per_digit).map(|i| (digit >> i) & 1 == 1));
+    }
+
+    if bits.iter().skip(width).any(|bit| *bit) {
+        return Err(format!("{} overflows {} bits", s, width));
+    }
+    bits.resize(width, false);
+
+    Ok(bits)
+}
+
+/// Parser for the compact value notation, see [`CompactValue`].
+struct CompactParser<'a> {
+    s: &'a str,
+    pos: usize,
+    /// The nesting depth of the value being parsed.
+    depth: usize,
+    /// The number of values produced so far, see [`MAX_COMPACT_ELEMENTS`].
+    elements: usize,
+}
+
+impl<'a> CompactParser<'a> {
+    fn error(&self, reason: impl Into<String>) -> TypeError {
+        TypeError::InvalidSyntax {
+            position: self.pos,
+            reason: reason.into(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.s[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), TypeError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", token)))
+        }
+    }
+
+    /// Consumes characters while `f` holds, returning them.
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn parse_number<T: core::str::FromStr>(&mut self) -> Result<T, TypeError> {
+        let start = self.pos;
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        digits.parse().map_err(|_| {
+            self.pos = start;
+            self.error("expected a number")
+        })
+    }
+
+    /// Accounts for `count` copies of `value`, returning an error if the total number of
+    /// values exceeds [`MAX_COMPACT_ELEMENTS`].
+    fn add_elements(&mut self, value: &Value, count: usize) -> Result<(), TypeError> {
+        fn value_count(value: &Value) -> usize {
+            match value {
+                Value::Array(v) | Value::Struct(v) => 1 + v.iter().map(value_count).sum::<usize>(),
+                Value::Named { inner, .. } => 1 + value_count(inner),
+                _ => 1,
+            }
+        }
+
+        self.elements = value_count(value)
+            .checked_mul(count)
+            .and_then(|n| n.checked_add(self.elements))
+            .filter(|n| *n <= MAX_COMPACT_ELEMENTS)
+            .ok_or_else(|| {
+                self.error(format!(
+                    "value has more than {MAX_COMPACT_ELEMENTS} elements"
+                ))
+            })?;
+        Ok(())
+    }
+
+    fn parse_value(&mut self) -> Result<Value, TypeError> {
+        if self.depth > MAX_TYPE_DEPTH {
+            return Err(self.error(format!(
+                "value is nested more than {MAX_TYPE_DEPTH} levels deep"
+            )));
+        }
+
+        self.depth += 1;
+        let value = self.parse_value_inner();
+        self.depth -= 1;
+        value
+    }
+
+    fn parse_value_inner(&mut self) -> Result<Value, TypeError> {
+        if self.eat("[") {
+            let mut elems: Vec<Value> = Vec::new();
+            loop {
+                let elem = self.parse_value()?;
+                let count = if self.eat("×") {
+                    self.parse_number::<usize>()?
+                } else {
+                    1
+                };
+                if count == 0 {
+                    return Err(self.error("run length must be non-zero"));
+                }
+                self.add_elements(&elem, count)?;
+                if let Some(first) = elems.first() {
+                    let (expected, actual) = (first.value_type(), elem.value_type());
+                    if expected != actual {
+                        return Err(TypeError::UnexpectedType { expected, actual });
+                    }
+                }
+                elems.extend(core::iter::repeat_n(elem, count));
+
+                if !self.eat(",") {
+                    break;
+                }
+            }
+            self.expect("]")?;
+
+            return Ok(Value::Array(elems));
+        }
+
+        if self.eat("{") {
+            let mut fields = vec![self.parse_value()?];
+            while self.eat(",") {
+                fields.push(self.parse_value()?);
+            }
+            self.expect("}")?;
+
+            return Ok(Value::Struct(fields));
+        }
+
+        let start = self.pos;
+        let value = match self.take_while(|c| c.is_ascii_alphanumeric()) {
+            "Bit" => {
+                self.expect("(")?;
+                let value = match self.take_while(|c| c.is_ascii_alphabetic()) {
+                    "true" => Value::Bit(true),
+                    "false" => Value::Bit(false),
+                    _ => return Err(self.error("expected `true` or `false`")),
+                };
+                self.expect(")")?;
+                value
+            }
+            "U8" => self.parse_scalar(Value::U8)?,
+            "U16" => self.parse_scalar(Value::U16)?,
+            "U32" => self.parse_scalar(Value::U32)?,
+            "U64" => self.parse_scalar(Value::U64)?,
+            "U128" => self.parse_scalar(Value::U128)?,
+            "Uint" => {
+                self.expect("<")?;
+                let width = self.parse_number::<usize>()?;
+                self.expect(">")?;
+                self.expect("(")?;
+                self.expect("0x")?;
+                let digits_pos = self.pos;
+                let digits = self.take_while(|c| c.is_ascii_hexdigit());
+                let Some(bytes) = hex_to_le_bytes(width, digits) else {
+                    self.pos = digits_pos;
+                    return Err(self.error("unexpected number of hex digits"));
+                };
+                self.expect(")")?;
+                Value::uint_from_le_bytes(width, &bytes)?
+            }
+            "Named" => {
+                self.expect("<")?;
+                let name = self.parse_name()?;
+                self.expect(">")?;
+                self.expect("(")?;
+                let inner = self.parse_value()?;
+                self.expect(")")?;
+                Value::Named {
+                    name,
+                    inner: Box::new(inner),
+                }
+            }
+            _ => {
+                self.pos = start;
+                return Err(self.error("expected a value"));
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// Parses the name of a [`Value::Named`], see [`write_compact_name`].
+    fn parse_name(&mut self) -> Result<String, TypeError> {
+        if !self.eat("\"") {
+            let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+            if name.is_empty() {
+                return Err(self.error("expected a name"));
+            }
+            return Ok(name.to_string());
+        }
+
+        let mut name = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(name);
+                }
+                '\\' => match chars.next() {
+                    Some((_, c @ ('"' | '\\'))) => name.push(c),
+                    _ => {
+                        self.pos += i;
+                        return Err(self.error("invalid escape in name"));
+                    }
+                },
+                c => name.push(c),
+            }
+        }
+        Err(self.error("unterminated name"))
+    }
+
+    fn parse_scalar<T: core::str::FromStr>(
+        &mut self,
+        f: impl FnOnce(T) -> Value,
+    ) -> Result<Value, TypeError> {
+        self.expect("(")?;
+        let value = self.parse_number::<T>()?;
+        self.expect(")")?;
+        Ok(f(value))
+    }
+}
+
+impl BitXor for Value {
+    type Output = Result<Value, TypeError>;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        &self ^ &rhs
+    }
+}
+
+impl BitXor<&Value> for &Value {
+    type Output = Result<Value, TypeError>;
+
+    fn bitxor(self, rhs: &Value) -> Self::Output {
+        self.zip_map(rhs, |a, b| {
+            Ok(match (a, b) {
+                (Value::Bit(a), Value::Bit(b)) => Value::Bit(a ^ b),
+                (Value::U8(a), Value::U8(b)) => Value::U8(a ^ b),
+                (Value::U16(a), Value::U16(b)) => Value::U16(a ^ b),
+                (Value::U32(a), Value::U32(b)) => Value::U32(a ^ b),
+                (Value::U64(a), Value::U64(b)) => Value::U64(a ^ b),
+                (Value::U128(a), Value::U128(b)) => Value::U128(a ^ b),
+                (Value::Uint(a), Value::Uint(b)) => {
+                    Value::Uint(a.iter().zip(b).map(|(a, b)| a ^ b).collect())
+                }
+                _ => unreachable!("leaf types should match"),
+            })
+        })
+    }
+}
+
+impl BitXor<&Value> for Value {
+    type Output = Result<Value, TypeError>;
+
+    fn bitxor(self, rhs: &Value) -> Self::Output {
+        &self ^ rhs
+    }
+}
+
+impl BitXor<Value> for &Value {
+    type Output = Result<Value, TypeError>;
+
+    fn bitxor(self, rhs: Value) -> Self::Output {
+        self ^ &rhs
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Value {
+    /// Converts the value into plain JSON, without type tags.
+    ///
+    /// Bits are encoded as booleans and integers up to 64 bits as numbers. `U128` values
+    /// are encoded as decimal strings to avoid precision loss in JavaScript, and `Uint`
+    /// values as big endian hex strings, eg. `"0x0201"`. Arrays and structs are encoded
+    /// as JSON arrays.
+    pub fn to_json_plain(&self) -> serde_json::Value {
+        match self {
+            Value::Bit(v) => serde_json::Value::Bool(*v),
+            Value::U8(v) => serde_json::Value::from(*v),
+            Value::U16(v) => serde_json::Value::from(*v),
+            Value::U32(v) => serde_json::Value::from(*v),
+            Value::U64(v) => serde_json::Value::from(*v),
+            Value::U128(v) => serde_json::Value::String(v.to_string()),
+            Value::Uint(v) => {
+                let digits: String = Value::uint_bits_to_le_bytes(v)
+                    .iter()
+                    .rev()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect();
+                serde_json::Value::String(format!("0x{}", digits))
+            }
+            Value::Array(v) | Value::Struct(v) => {
+                serde_json::Value::Array(v.iter().map(|v| v.to_json_plain()).collect())
+            }
+            Value::Named { inner, .. } => inner.to_json_plain(),
+        }
+    }
+
+    /// Converts plain JSON into a value of the given type.
+    ///
+    /// This is the inverse of [`Value::to_json_plain`], and rejects numbers which are out
+    /// of range of the type. `U128` values may also be given as numbers.
+    ///
+    /// # Arguments
+    ///
+    /// * `ty` - The type of the value.
+    /// * `json` - The JSON to convert.
+    pub fn from_json_plain(ty: &ValueType, json: &serde_json::Value) -> Result<Value, TypeError> {
+        let invalid = |reason: &str| TypeError::InvalidJson {
+            expected: ty.clone(),
+            reason: reason.to_string(),
+        };
+        let number = || json.as_u64().ok_or_else(|| invalid("expected a number"));
+        let out_of_range = |_| invalid("number out of range");
+
+        Ok(match ty {
+            ValueType::Bit => Value::Bit(
+                json.as_bool()
+                    .ok_or_else(|| invalid("expected a boolean"))?,
+            ),
+            ValueType::U8 => Value::U8(number()?.try_into().map_err(out_of_range)?),
+            ValueType::U16 => Value::U16(number()?.try_into().map_err(out_of_range)?),
+            ValueType::U32 => Value::U32(number()?.try_into().map_err(out_of_range)?),
+            ValueType::U64 => Value::U64(number()?),
+            ValueType::U128 => match json.as_str() {
+                Some(s) => Value::U128(
+                    s.parse()
+                        .map_err(|_| invalid("expected a decimal string"))?,
+                ),
+                None => Value::U128(number()?.into()),
+            },
+            ValueType::Uint(width) => {
+                let bytes = json
+                    .as_str()
+                    .and_then(|s| s.strip_prefix("0x"))
+                    .and_then(|digits| hex_to_le_bytes(*width, digits))
+                    .ok_or_else(|| invalid("expected a hex string"))?;
+                Value::uint_from_le_bytes(*width, &bytes)?
+            }
+            ValueType::Array(elem, len) => {
+                let v = json
+                    .as_array()
+                    .filter(|v| v.len() == *len)
+                    .ok_or_else(|| invalid("expected an array of matching length"))?;
+                Value::Array(
+                    v.iter()
+                        .map(|v| Value::from_json_plain(elem, v))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+            }
+            ValueType::Struct(fields) => {
+                let v = json
+                    .as_array()
+                    .filter(|v| v.len() == fields.len())
+                    .ok_or_else(|| invalid("expected an array of matching length"))?;
+                Value::Struct(
+                    fields
+                        .iter()
+                        .zip(v)
+                        .map(|(ty, v)| Value::from_json_plain(ty, v))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+            }
+            ValueType::Named { name, inner } => Value::Named {
+                name: name.clone(),
+                inner: Box::new(Value::from_json_plain(inner, json)?),
+            },
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ValueType {
+    /// Returns a JSON Schema fragment describing values of this type, as encoded by
+    /// [`Value::to_json_plain`].
+    ///
+    /// Integers up to 64 bits are described as bounded integers, while `U128` and `Uint`
+    /// values are described as strings matching their encoding. Named types carry their
+    /// name as the schema title.
+    pub fn to_json_schema(&self) -> serde_json::Value {
+        use serde_json::json;
+
+        match self {
+            ValueType::Bit => json!({ "type": "boolean" }),
+            ValueType::U8 => json!({ "type": "integer", "minimum": 0, "maximum": u8::MAX }),
+            ValueType::U16 => json!({ "type": "integer", "minimum": 0, "maximum": u16::MAX }),
+            ValueType::U32 => json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX }),
+            ValueType::U64 => json!({ "type": "integer", "minimum": 0, "maximum": u64::MAX }),
+            ValueType::U128 => json!({ "type": "string", "pattern": "^[0-9]+$" }),
+            ValueType::Uint(_) => json!({ "type": "string", "pattern": "^0x[0-9a-f]*$" }),
+            ValueType::Array(ty, len) => json!({
+                "type": "array",
+                "items": ty.to_json_schema(),
+                "minItems": len,
+                "maxItems": len,
+            }),
+            ValueType::Struct(fields) => json!({
+                "type": "array",
+                "prefixItems": fields.iter().map(|ty| ty.to_json_schema()).collect::<Vec<_>>(),
+                "items": false,
+                "minItems": fields.len(),
+                "maxItems": fields.len(),
+            }),
+            ValueType::Named { name, inner } => {
+                let mut schema = inner.to_json_schema();
+                schema["title"] = json!(name);
+                schema
+            }
+        }
+    }
+}
+
+/// The maximum nesting depth of arbitrary array types.
+#[cfg(feature = "arbitrary")]
+const ARBITRARY_MAX_DEPTH: usize = 3;
+
+/// The maximum length of arbitrary array types.
+#[cfg(feature = "arbitrary")]
+const ARBITRARY_MAX_ARRAY_LEN: usize = 8;
+
+/// The maximum number of fields of arbitrary struct types.
+#[cfg(feature = "arbitrary")]
+const ARBITRARY_MAX_STRUCT_FIELDS: usize = 4;
+
+/// The maximum width of arbitrary `Uint` types.
+#[cfg(feature = "arbitrary")]
+const ARBITRARY_MAX_UINT_WIDTH: usize = 256;
+
+#[cfg(feature = "arbitrary")]
+impl ValueType {
+    fn arbitrary_with_depth(
+        u: &mut arbitrary::Unstructured<'_>,
+        depth: usize,
+    ) -> arbitrary::Result<Self> {
+        // Only allow arrays and structs while we are below the maximum nesting depth.
+        let max_kind: u8 = if depth < ARBITRARY_MAX_DEPTH { 8 } else { 6 };
+
+        Ok(match u.int_in_range(0..=max_kind)? {
+            0 => ValueType::Bit,
+            1 => ValueType::U8,
+            2 => ValueType::U16,
+            3 => ValueType::U32,
+            4 => ValueType::U64,
+            5 => ValueType::U128,
+            6 => ValueType::Uint(u.int_in_range(1..=ARBITRARY_MAX_UINT_WIDTH)?),
+            7 => {
+                let ty = ValueType::arbitrary_with_depth(u, depth + 1)?;
+                let len = u.int_in_range(1..=ARBITRARY_MAX_ARRAY_LEN)?;
+                ValueType::Array(Box::new(ty), len)
+            }
+            _ => {
+                let len = u.int_in_range(1..=ARBITRARY_MAX_STRUCT_FIELDS)?;
+                ValueType::Struct(
+                    (0..len)
+                        .map(|_| ValueType::arbitrary_with_depth(u, depth + 1))
+                        .collect::<arbitrary::Result<Vec<_>>>()?,
+                )
+            }
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ValueType {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        ValueType::arbitrary_with_depth(u, 0)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl Value {
+    /// Generates an arbitrary value of the provided type.
+    ///
+    /// # Arguments
+    ///
+    /// * `u` - The source of unstructured data.
+    /// * `ty` - The type of the value to generate.
+    pub fn arbitrary_with_type(
+        u: &mut arbitrary::Unstructured<'_>,
+        ty: &ValueType,
+    ) -> arbitrary::Result<Self> {
+        Ok(match ty {
+            ValueType::Bit => Value::Bit(u.arbitrary()?),
+            ValueType::U8 => Value::U8(u.arbitrary()?),
+            ValueType::U16 => Value::U16(u.arbitrary()?),
+            ValueType::U32 => Value::U32(u.arbitrary()?),
+            ValueType::U64 => Value::U64(u.arbitrary()?),
+            ValueType::U128 => Value::U128(u.arbitrary()?),
+            ValueType::Uint(width) => Value::Uint(
+                (0..*width)
+                    .map(|_| u.arbitrary())
+                    .collect::<arbitrary::Result<Vec<_>>>()?,
+            ),
+            ValueType::Array(ty, len) => Value::Array(
+                (0..*len)
+                    .map(|_| Value::arbitrary_with_type(u, ty))
+                    .collect::<arbitrary::Result<Vec<_>>>()?,
+            ),
+            ValueType::Struct(fields) => Value::Struct(
+                fields
+                    .iter()
+                    .map(|ty| Value::arbitrary_with_type(u, ty))
+                    .collect::<arbitrary::Result<Vec<_>>>()?,
+            ),
+            ValueType::Named { name, inner } => Value::Named {
+                name: name.clone(),
+                inner: Box::new(Value::arbitrary_with_type(u, inner)?),
+            },
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Value {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let ty = ValueType::arbitrary(u)?;
+        Value::arbitrary_with_type(u, &ty)
+    }
+}
+
+/// The order of the bits within each scalar of a value.
+///
+/// Aggregates are ordered element by element in either case, only the order of the bits
+/// within each scalar differs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// Least significant bit first, as yielded by [`IntoBits::into_iter_lsb0`].
+    Lsb0,
+    /// Most significant bit first, as yielded by [`IntoBits::into_iter_msb0`].
+    Msb0,
+}
+
+/// The byte order of a multi-byte value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endian {
+    /// Most significant byte first.
+    Big,
+    /// Least significant byte first.
+    Little,
+}
+
+macro_rules! impl_convert_bytes {
+    ($ty:ident, $len:expr) => {
+        impl $ty {
+            /// Create a value from its representation as a byte array in the given byte order.
+            pub fn from_bytes(bytes: [U8; $len], endian: Endian) -> Self {
+                match endian {
+                    Endian::Big => {
+                        $ty(core::array::from_fn(|i| bytes[$len - (i / 8) - 1].0[i % 8]))
+                    }
+                    Endian::Little => $ty(core::array::from_fn(|i| bytes[i / 8].0[i % 8])),
+                }
+            }
+
+            /// Returns the representation of this type as a byte array in the given byte order.
+            pub fn to_bytes(self, endian: Endian) -> [U8; $len] {
+                match endian {
+                    Endian::Big => core::array::from_fn(|i| {
+                        U8(core::array::from_fn(|j| self.0[($len - i - 1) * 8 + j]))
+                    }),
+                    Endian::Little => {
+                        core::array::from_fn(|i| U8(core::array::from_fn(|j| self.0[i * 8 + j])))
+                    }
+                }
+            }
+
+            /// Create a value from its representation as a byte array in big endian.
+            pub fn from_be_bytes(bytes: [U8; $len]) -> Self {
+                Self::from_bytes(bytes, Endian::Big)
+            }
+
+            /// Returns the representation of this type as a byte array in big endian.
+            pub fn to_be_bytes(self) -> [U8; $len] {
+                self.to_bytes(Endian::Big)
+            }
+
+            /// Create a value from its representation as a byte array in little endian.
+            pub fn from_le_bytes(bytes: [U8; $len]) -> Self {
+                Self::from_bytes(bytes, Endian::Little)
+            }
+
+            /// Returns the representation of this type as a byte array in little endian.
+            pub fn to_le_bytes(self) -> [U8; $len] {
+                self.to_bytes(Endian::Little)
+            }
+        }
+    };
+}
+
+impl_convert_bytes!(U8, 1);
+impl_convert_bytes!(U16, 2);
+impl_convert_bytes!(U32, 4);
+impl_convert_bytes!(U64, 8);
+impl_convert_bytes!(U128, 16);
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use mpz_circuits_macros::{test_circ, trace};
+
+    use crate::{CircuitBuilder, Tracer};
+
+    use super::*;
+
+    #[trace]
+    fn to_be_bytes(a: u128) -> [u8; 16] {
+        a.to_be_bytes()
+    }
+
+    #[trace]
+    fn to_le_bytes(a: u128) -> [u8; 16] {
+        a.to_le_bytes()
+    }
+
+    #[test]
+    fn test_convert_bytes() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u128>();
+        let a_bytes = to_be_bytes_trace(builder.state(), a);
+        builder.add_output(a_bytes);
+        let circ = builder.build().unwrap();
+
+        test_circ!(circ, to_be_bytes, fn(69u128) -> [u8; 16]);
+
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u128>();
+        let a_bytes = to_le_bytes_trace(builder.state(), a);
+        builder.add_output(a_bytes);
+        let circ = builder.build().unwrap();
+
+        test_circ!(circ, to_le_bytes, fn(69u128) -> [u8; 16]);
+    }
+
+    #[test]
+    fn test_mask() {
+        assert_eq!(
+            Value::low_mask(&ValueType::U16, 4).unwrap(),
+            Value::U16(0x000f)
+        );
+        assert_eq!(
+            Value::high_mask(&ValueType::U16, 4).unwrap(),
+            Value::U16(0xf000)
+        );
+        assert_eq!(Value::low_mask(&ValueType::U8, 0).unwrap(), Value::U8(0));
+        assert_eq!(
+            Value::high_mask(&ValueType::U128, 128).unwrap(),
+            Value::U128(u128::MAX)
+        );
+        assert_eq!(
+            Value::high_mask(&ValueType::Uint(12), 4).unwrap(),
+            Value::uint_from_le_bytes(12, &[0x00, 0x0f]).unwrap()
+        );
+
+        assert!(matches!(
+            Value::low_mask(&ValueType::U8, 9),
+            Err(TypeError::InvalidLength { .. })
+        ));
+        assert!(matches!(
+            Value::low_mask(&ValueType::Bit, 1),
+            Err(TypeError::NotInteger { .. })
+        ));
+    }
+
+    #[test]
+    fn test_promote() {
+        assert_eq!(
+            Value::promote(&Value::U8(0xff), &Value::U32(1)).unwrap(),
+            (Value::U32(0xff), Value::U32(1))
+        );
+        assert_eq!(
+            Value::promote(&Value::U64(2), &Value::U16(3)).unwrap(),
+            (Value::U64(2), Value::U64(3))
+        );
+        assert_eq!(
+            Value::promote(&Value::U8(1), &Value::U8(2)).unwrap(),
+            (Value::U8(1), Value::U8(2))
+        );
+
+        let uint = Value::uint_from_le_bytes(12, &[0x34, 0x02]).unwrap();
+        assert_eq!(
+            Value::promote(&uint, &Value::U16(0)).unwrap(),
+            (Value::U16(0x0234), Value::U16(0))
+        );
+
+        assert!(matches!(
+            Value::promote(&Value::Bit(true), &Value::U8(1)),
+            Err(TypeError::NotInteger { .. })
+        ));
+        assert!(matches!(
+            Value::promote(&Value::U8(1), &Value::from([1u8])),
+            Err(TypeError::NotInteger { .. })
+        ));
+        assert!(matches!(
+            Value::promote(&Value::U8(1), &Value::Uint(vec![false; 8])),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_saturating_cast() {
+        // in range
+        assert_eq!(
+            Value::U32(0xFF).saturating_cast(&ValueType::U8).unwrap(),
+            Value::U8(0xFF)
+        );
+        // overflow
+        assert_eq!(
+            Value::U32(0x1_0000)
+                .saturating_cast(&ValueType::U8)
+                .unwrap(),
+            Value::U8(0xFF)
+        );
+        // widening
+        assert_eq!(
+            Value::U8(42).saturating_cast(&ValueType::U64).unwrap(),
+            Value::U64(42)
+        );
+
+        assert!(Value::Bit(true).saturating_cast(&ValueType::U8).is_err());
+        assert!(Value::Array(vec![Value::U8(1)])
+            .saturating_cast(&ValueType::U8)
+            .is_err());
+        assert!(Value::U8(1).saturating_cast(&ValueType::Bit).is_err());
+        assert!(matches!(
+            Value::Uint(vec![false; 192]).saturating_cast(&ValueType::U8),
+            Err(TypeError::InvalidLength {
+                expected: 128,
+                actual: 192,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_validate_repr() {
+        let builder = CircuitBuilder::new();
+        let ty = ValueType::Array(Box::new(ValueType::Array(Box::new(ValueType::U8), 2)), 2);
+        let repr = builder.add_input_by_type(ty.clone());
+
+        assert!(ty.validate_repr(&repr).is_ok());
+
+        // wrong width
+        let wrong_width =
+            ValueType::Array(Box::new(ValueType::Array(Box::new(ValueType::U16), 2)), 2);
+        let err = wrong_width.validate_repr(&repr).unwrap_err();
+        assert!(matches!(
+            err,
+            TypeError::InvalidRepr { path, expected: ValueType::U16, actual: ValueType::U8 }
+                if path == vec![0, 0]
+        ));
+
+        // wrong array length
+        let wrong_len = ValueType::Array(Box::new(ValueType::Array(Box::new(ValueType::U8), 3)), 2);
+        let err = wrong_len.validate_repr(&repr).unwrap_err();
+        assert!(matches!(
+            err,
+            TypeError::InvalidRepr { path, .. } if path == vec![0]
+        ));
+    }
+
+    #[test]
+    fn test_node_range() {
+        let builder = CircuitBuilder::new();
+        let _ = builder.add_input_by_type(ValueType::U8);
+        let mut repr = builder.add_input_by_type(ValueType::new_array::<u8>(2));
+
+        // ids 0 and 1 are reserved for constants, followed by the first input
+        assert_eq!(repr.node_range(), Some((10, 25)));
+        assert!(repr.contains_node(10));
+        assert!(repr.contains_node(25));
+        assert!(!repr.contains_node(9));
+        assert!(!repr.contains_node(26));
+
+        repr.shift_left(10);
+
+        assert_eq!(repr.node_range(), Some((0, 15)));
+        assert!(repr.contains_node(0));
+        assert!(!repr.contains_node(16));
+    }
+
+    #[test]
+    fn test_zip_map() {
+        let a = Value::Array(vec![Value::from([1u8, 2u8]), Value::from([3u8, 4u8])]);
+        let b = Value::Array(vec![Value::from([10u8, 20u8]), Value::from([30u8, 40u8])]);
+
+        let sum = a
+            .zip_map(&b, |a, b| match (a, b) {
+                (Value::U8(a), Value::U8(b)) => Ok(Value::U8(a.wrapping_add(*b))),
+                _ => unreachable!(),
+            })
+            .unwrap();
+
+        assert_eq!(
+            sum,
+            Value::Array(vec![Value::from([11u8, 22u8]), Value::from([33u8, 44u8]),])
+        );
+
+        // length mismatch
+        let c = Value::Array(vec![Value::from([1u8, 2u8]), Value::from([3u8])]);
+        assert!(a.zip_map(&c, |a, _| Ok(a.clone())).is_err());
+
+        // type mismatch
+        let d = Value::Array(vec![Value::from([1u8, 2u8]), Value::from([3u16, 4u16])]);
+        assert!(a.zip_map(&d, |a, _| Ok(a.clone())).is_err());
+
+        // xor is implemented on top of zip_map
+        assert_eq!(
+            (&a ^ &b).unwrap(),
+            Value::Array(vec![
+                Value::from([1u8 ^ 10, 2u8 ^ 20]),
+                Value::from([3u8 ^ 30, 4u8 ^ 40]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_reverse_bits() {
+        assert_eq!(
+            Value::U8(0b0000_0001).reverse_bits().unwrap(),
+            Value::U8(0b1000_0000)
+        );
+        assert_eq!(Value::Bit(true).reverse_bits().unwrap(), Value::Bit(true));
+        assert_eq!(
+            Value::from([1u16, 2u16]).reverse_bits().unwrap(),
+            Value::from([0x8000u16, 0x4000u16])
+        );
+
+        let mut rng = rand::thread_rng();
+        for ty in [
+            ValueType::U8,
+            ValueType::U32,
+            ValueType::U128,
+            ValueType::new_array::<u64>(4),
+        ] {
+            let value = Value::random(&mut rng, &ty);
+            assert_eq!(value.reverse_bits().unwrap().reverse_bits().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_from_ref_and_nonzero() {
+        use std::num::{NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+
+        assert_eq!(Value::from(&true), Value::from(true));
+        assert_eq!(Value::from(&42u8), Value::from(42u8));
+        assert_eq!(Value::from(&42u16), Value::from(42u16));
+        assert_eq!(Value::from(&42u32), Value::from(42u32));
+        assert_eq!(Value::from(&42u64), Value::from(42u64));
+        assert_eq!(Value::from(&42u128), Value::from(42u128));
+
+        assert_eq!(Value::from(NonZeroU8::new(42).unwrap()), Value::from(42u8));
+        assert_eq!(
+            Value::from(NonZeroU16::new(42).unwrap()),
+            Value::from(42u16)
+        );
+        assert_eq!(
+            Value::from(NonZeroU32::new(42).unwrap()),
+            Value::from(42u32)
+        );
+        assert_eq!(
+            Value::from(NonZeroU64::new(42).unwrap()),
+            Value::from(42u64)
+        );
+        assert_eq!(
+            Value::from(NonZeroU128::new(42).unwrap()),
+            Value::from(42u128)
+        );
+    }
+
+    #[test]
+    fn test_bit_len() {
+        let mut rng = rand::thread_rng();
+        for ty in [
+            ValueType::Bit,
+            ValueType::U8,
+            ValueType::U16,
+            ValueType::U32,
+            ValueType::U64,
+            ValueType::U128,
+            ValueType::new_array::<u32>(3),
+            ValueType::Array(Box::new(ValueType::new_array::<bool>(3)), 5),
+        ] {
+            let value = Value::random(&mut rng, &ty);
+            assert_eq!(value.bit_len(), value.value_type().len());
+            assert_eq!(value.byte_len(), value.value_type().len().div_ceil(8));
+        }
+
+        assert_eq!(Value::Bit(true).byte_len(), 1);
+        assert_eq!(Value::from([true; 9]).byte_len(), 2);
+    }
+
+    #[test]
+    fn test_uint() {
+        let ty = ValueType::Uint(24);
+        let value = Value::uint_from_le_bytes(24, &[0x01, 0x02, 0x03]).unwrap();
+
+        assert_eq!(value.value_type(), ty);
+        assert_eq!(value.bit_len(), 24);
+        assert_eq!(value.to_string(), "Uint<24>(0x030201)");
+
+        // bits round trip
+        let builder = CircuitBuilder::new();
+        let repr = builder.add_input_by_type(ty.clone());
+        let bits: Vec<bool> = value.clone().into_iter_lsb0().collect();
+        assert_eq!(bits.len(), 24);
+        assert_eq!(repr.from_bin_repr(&bits).unwrap(), value);
+
+        // bytes round trip
+        assert_eq!(value.uint_to_le_bytes().unwrap(), vec![0x01, 0x02, 0x03]);
+
+        // padded bytes round trip
+        let value = Value::uint_from_le_bytes(20, &[0xff, 0xff, 0x0f]).unwrap();
+        assert_eq!(value, Value::Uint(vec![true; 20]));
+        assert_eq!(value.uint_to_le_bytes().unwrap(), vec![0xff, 0xff, 0x0f]);
+        assert!(Value::uint_from_le_bytes(20, &[0xff, 0xff, 0xff]).is_err());
+        assert!(Value::uint_from_le_bytes(20, &[0xff, 0xff]).is_err());
+
+        let mut rng = rand::thread_rng();
+        let value = Value::random(&mut rng, &ValueType::Uint(40));
+        assert_eq!(value.value_type(), ValueType::Uint(40));
+        assert_eq!(
+            Value::uint_from_le_bytes(40, &value.uint_to_le_bytes().unwrap()).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_into_byte_array() {
+        assert_eq!(
+            Value::U32(0xAABBCCDD).into_byte_array().unwrap(),
+            Value::Array(vec![
+                Value::U8(0xAA),
+                Value::U8(0xBB),
+                Value::U8(0xCC),
+                Value::U8(0xDD)
+            ])
+        );
+        assert_eq!(
+            Value::U32(0xAABBCCDD).into_le_byte_array().unwrap(),
+            Value::from([0xDDu8, 0xCC, 0xBB, 0xAA])
+        );
+        assert_eq!(
+            Value::U128(1).into_byte_array().unwrap(),
+            Value::from(1u128.to_be_bytes())
+        );
+        assert_eq!(
+            Value::uint_from_le_bytes(24, &[1, 2, 3])
+                .unwrap()
+                .into_byte_array()
+                .unwrap(),
+            Value::from([3u8, 2, 1])
+        );
+
+        assert!(Value::Bit(true).into_byte_array().is_err());
+        assert!(Value::from([1u8, 2]).into_byte_array().is_err());
+        assert!(Value::Uint(vec![true; 12]).into_byte_array().is_err());
+
+        let value = Value::U32(0xAABBCCDD);
+        assert_eq!(
+            value.to_byte_array().unwrap(),
+            Value::from([0xAAu8, 0xBB, 0xCC, 0xDD])
+        );
+        assert_eq!(
+            value.to_le_byte_array().unwrap(),
+            Value::from([0xDDu8, 0xCC, 0xBB, 0xAA])
+        );
+        assert!(Value::Bit(true).to_byte_array().is_err());
+    }
+
+    #[test]
+    fn test_from_byte_array() {
+        let bytes = Value::from([1u8, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(
+            Value::from_byte_array(&bytes, &ValueType::U64).unwrap(),
+            Value::U64(0x0102030405060708)
+        );
+        assert_eq!(
+            Value::from_byte_array(&bytes, &ValueType::Uint(64)).unwrap(),
+            Value::Uint(0x0102030405060708u64.into_lsb0_vec())
+        );
+
+        let value = Value::U128(0x0123456789ABCDEF0123456789ABCDEF);
+        assert_eq!(
+            Value::from_byte_array(&value.to_byte_array().unwrap(), &ValueType::U128).unwrap(),
+            value
+        );
+
+        // wrong byte count
+        assert!(Value::from_byte_array(&bytes, &ValueType::U32).is_err());
+        // not an integer type
+        assert!(Value::from_byte_array(&bytes, &ValueType::new_array::<u8>(8)).is_err());
+        // not bytes
+        assert!(Value::from_byte_array(&Value::from([1u16, 2, 3, 4]), &ValueType::U64).is_err());
+    }
+
+    #[test]
+    fn test_value_iter() {
+        let value = Value::Array(vec![
+            Value::from([1u8, 2]),
+            Value::from([3u8, 4]),
+            Value::from([5u8, 6]),
+        ]);
+
+        assert_eq!(value.iter().count(), 3);
+        assert_eq!(value.iter().flat_map(|v| v.iter()).count(), 6);
+        assert_eq!(value.iter().next(), Some(&Value::from([1u8, 2])));
+
+        let elements: Vec<Value> = value.into_iter().collect();
+        assert_eq!(elements.len(), 3);
+        assert_eq!(elements[2], Value::from([5u8, 6]));
+
+        // scalars yield themselves
+        let scalar = Value::U32(42);
+        assert_eq!(scalar.iter().collect::<Vec<_>>(), vec![&Value::U32(42)]);
+        assert_eq!(scalar.into_iter().collect::<Vec<_>>(), vec![Value::U32(42)]);
+    }
+
+    #[test]
+    fn test_into_elements() {
+        let value = Value::from([1u16, 2, 3]);
+
+        assert_eq!(
+            value.as_elements().unwrap(),
+            &[Value::U16(1), Value::U16(2), Value::U16(3)]
+        );
+        assert_eq!(
+            value.into_elements().unwrap(),
+            vec![Value::U16(1), Value::U16(2), Value::U16(3)]
+        );
+
+        assert!(matches!(
+            Value::U32(42).as_elements(),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::Struct(vec![Value::U8(1)]).into_elements(),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let value = Value::Struct(vec![Value::from([[1u8, 2], [3, 4]]), Value::U16(5)]);
+        assert_eq!(value.canonicalize().unwrap(), value);
+
+        let value = Value::Struct(vec![Value::Array(vec![
+            Value::from([1u8, 2]),
+            Value::Array(vec![Value::U8(3), Value::U16(4)]),
+        ])]);
+        let Err(TypeError::ArrayElement { index: 1, source }) = value.canonicalize() else {
+            panic!("expected an array element error");
+        };
+        assert!(matches!(
+            *source,
+            TypeError::ArrayElement { index: 1, source }
+                if matches!(*source, TypeError::UnexpectedType { expected: ValueType::U8, actual: ValueType::U16 })
+        ));
+    }
+
+    #[test]
+    fn test_value_from_bools() {
+        let expected = Value::Array(vec![Value::Bit(true), Value::Bit(false), Value::Bit(true)]);
+
+        let value = Value::from(&[true, false, true][..]);
+        assert_eq!(value.value_type(), ValueType::new_array::<bool>(3));
+        assert_eq!(value, expected);
+
+        assert_eq!(Value::from(vec![true, false, true]), expected);
+        assert_eq!(Value::from([true, false, true]), expected);
+    }
+
+    #[test]
+    fn test_elements_all_equal() {
+        assert!(Value::from([7u8; 4]).elements_all_equal().unwrap());
+        assert!(Value::from([[1u16, 2], [1, 2]])
+            .elements_all_equal()
+            .unwrap());
+        assert!(Value::Array(Vec::new()).elements_all_equal().unwrap());
+
+        assert!(!Value::from([7u8, 7, 8]).elements_all_equal().unwrap());
+        assert!(!Value::from([[1u16, 2], [1, 3]])
+            .elements_all_equal()
+            .unwrap());
+
+        assert!(matches!(
+            Value::U32(42).elements_all_equal(),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_const_value_type() {
+        const BIT: ValueType = ValueType::bit();
+        const INTS: [ValueType; 5] = [
+            ValueType::u8(),
+            ValueType::u16(),
+            ValueType::u32(),
+            ValueType::u64(),
+            ValueType::u128(),
+        ];
+        const UINT: ValueType = ValueType::uint(12);
+
+        assert_eq!(BIT, ValueType::new::<bool>());
+        assert_eq!(
+            INTS,
+            [
+                ValueType::new::<u8>(),
+                ValueType::new::<u16>(),
+                ValueType::new::<u32>(),
+                ValueType::new::<u64>(),
+                ValueType::new::<u128>(),
+            ]
+        );
+        assert_eq!(UINT, ValueType::Uint(12));
+    }
+
+    #[test]
+    fn test_try_new_array() {
+        assert!(matches!(
+            ValueType::try_new_array::<u8>(0),
+            Err(TypeError::ZeroLengthArray)
+        ));
+        assert_eq!(
+            ValueType::try_new_array::<u8>(4).unwrap(),
+            ValueType::new_array::<u8>(4)
+        );
+    }
+
+    #[test]
+    fn test_eq_unordered() {
+        let a = Value::Array(vec![Value::from([1u8, 2]), Value::from([3u8, 4])]);
+        let b = Value::Array(vec![Value::from([4u8, 3]), Value::from([2u8, 1])]);
+        let c = Value::Array(vec![Value::from([1u8, 2]), Value::from([3u8, 5])]);
+
+        assert!(a.eq_unordered(&b).unwrap());
+        assert!(!a.eq_unordered(&c).unwrap());
+
+        // duplicates must match one to one
+        let d = Value::from([1u8, 1, 2]);
+        let e = Value::from([1u8, 2, 2]);
+        assert!(!d.eq_unordered(&e).unwrap());
+
+        assert!(Value::U8(1).eq_unordered(&Value::U8(1)).unwrap());
+        assert!(!Value::U8(1).eq_unordered(&Value::U8(2)).unwrap());
+
+        // type mismatch
+        assert!(a.eq_unordered(&Value::from([1u8, 2, 3, 4])).is_err());
+        assert!(Value::from([1u8, 2])
+            .eq_unordered(&Value::from([1u8, 2, 3]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_from_bin_repr_as() {
+        let builder = CircuitBuilder::new();
+        let repr: BinaryRepr = builder.add_input::<[u8; 4]>().into();
+
+        let bytes = [0x12u8, 0x34, 0x56, 0x78];
+        let bits: Vec<bool> = Value::from(bytes).into_iter_lsb0().collect();
+
+        assert_eq!(
+            repr.from_bin_repr_as(&bits, ValueType::U32, Endian::Big)
+                .unwrap(),
+            Value::U32(0x12345678)
+        );
+        assert_eq!(
+            repr.from_bin_repr_as(&bits, ValueType::U32, Endian::Little)
+                .unwrap(),
+            Value::U32(0x78563412)
+        );
+        assert_eq!(
+            repr.from_bin_repr_as(&bits, ValueType::Uint(32), Endian::Little)
+                .unwrap(),
+            Value::uint_from_le_bytes(32, &bytes).unwrap()
+        );
+        assert_eq!(
+            repr.from_bin_repr_as(&bits, ValueType::new_array::<u8>(4), Endian::Big)
+                .unwrap(),
+            Value::from(bytes)
+        );
+        assert!(matches!(
+            repr.from_bin_repr_as(&bits, ValueType::U64, Endian::Big),
+            Err(TypeError::InvalidLength { .. })
+        ));
+        assert!(matches!(
+            repr.from_bin_repr_as(&bits, ValueType::new_array::<u16>(2), Endian::Big),
+            Err(TypeError::NotInteger { .. })
+        ));
+
+        let repr: BinaryRepr = builder.add_input::<u32>().into();
+        assert!(matches!(
+            repr.from_bin_repr_as(&bits, ValueType::new_array::<u8>(4), Endian::Big),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_get_flat() {
+        let mut value = Value::from([[0u8, 1, 2], [3, 4, 5], [6, 7, 8]]);
+
+        assert_eq!(value.get_flat(0), Some(&Value::U8(0)));
+        assert_eq!(value.get_flat(5), Some(&Value::U8(5)));
+        assert_eq!(value.get_flat(8), Some(&Value::U8(8)));
+        assert_eq!(value.get_flat(9), None);
+
+        *value.get_flat_mut(5).unwrap() = Value::U8(42);
+        assert_eq!(value, Value::from([[0u8, 1, 2], [3, 4, 42], [6, 7, 8]]));
+        assert!(value.get_flat_mut(9).is_none());
+
+        let value = Value::Struct(vec![Value::Bit(true), Value::from([1u16, 2])]);
+        assert_eq!(value.get_flat(2), Some(&Value::U16(2)));
+        assert_eq!(Value::U32(1).get_flat(0), Some(&Value::U32(1)));
+        assert_eq!(Value::U32(1).get_flat(1), None);
+    }
+
+    #[test]
+    fn test_try_fold() {
+        let elems: Vec<[u16; 3]> = vec![[1, 2, 3], [400, 500, 600], [u16::MAX; 3]];
+        let value = Value::Array(elems.iter().map(|v| Value::from(*v)).collect());
+
+        let sum = value
+            .try_fold(0u64, |acc, v| match v {
+                Value::U16(v) => Ok(acc + *v as u64),
+                v => Err(TypeError::UnexpectedType {
+                    expected: ValueType::U16,
+                    actual: v.value_type(),
+                }),
+            })
+            .unwrap();
+
+        let expected: u64 = elems.iter().flatten().map(|v| *v as u64).sum();
+        assert_eq!(sum, expected);
+
+        let leaves = value
+            .try_fold(Vec::new(), |mut acc, v| {
+                acc.push(v.clone());
+                Ok(acc)
+            })
+            .unwrap();
+        let bits: Vec<bool> = leaves
+            .into_iter()
+            .flat_map(|v| v.into_iter_lsb0())
+            .collect();
+        assert_eq!(bits, value.clone().into_iter_lsb0().collect::<Vec<_>>());
+
+        assert!(Value::from([1u8, 2])
+            .try_fold(0u64, |_, v| Err(TypeError::UnexpectedType {
+                expected: ValueType::U16,
+                actual: v.value_type(),
+            }))
+            .is_err());
+    }
+
+    #[test]
+    fn test_checked_add() {
+        assert_eq!(
+            Value::U8(254).checked_add(&Value::U8(1)).unwrap(),
+            Some(Value::U8(255))
+        );
+        assert_eq!(Value::U8(255).checked_add(&Value::U8(1)).unwrap(), None);
+        assert_eq!(Value::U8(128).checked_add(&Value::U8(128)).unwrap(), None);
+
+        assert_eq!(
+            Value::from([1u8, 254])
+                .checked_add(&Value::from([2u8, 1]))
+                .unwrap(),
+            Some(Value::from([3u8, 255]))
+        );
+        assert_eq!(
+            Value::from([1u8, 255])
+                .checked_add(&Value::from([2u8, 1]))
+                .unwrap(),
+            None
+        );
+
+        let a = Value::uint_from_le_bytes(12, &[0xff, 0x03]).unwrap();
+        let b = Value::uint_from_le_bytes(12, &[0x01, 0x04]).unwrap();
+        assert_eq!(
+            a.checked_add(&b).unwrap(),
+            Some(Value::uint_from_le_bytes(12, &[0x00, 0x08]).unwrap())
+        );
+        let c = Value::uint_from_le_bytes(12, &[0x00, 0x08]).unwrap();
+        assert_eq!(c.checked_add(&c).unwrap(), None);
+
+        assert!(Value::U8(1).checked_add(&Value::U16(1)).is_err());
+        assert!(Value::Bit(true).checked_add(&Value::Bit(false)).is_err());
+    }
+
+    #[test]
+    fn test_binary_repr_from_nodes() {
+        let builder = CircuitBuilder::new();
+        let input: BinaryRepr = builder.add_input::<[u8; 4]>().into();
+        let nodes: Vec<_> = input.iter().copied().collect();
+        assert_eq!(nodes.len(), 32);
+
+        let ty = ValueType::new_array::<u8>(4);
+        let repr = BinaryRepr::from_nodes(&ty, &nodes).unwrap();
+        assert_eq!(repr.value_type(), ty);
+        assert_eq!(repr.iter().count(), 32);
+
+        assert!(matches!(
+            BinaryRepr::from_nodes(&ty, &nodes[..31]),
+            Err(TypeError::InvalidLength {
+                expected: 32,
+                actual: 31,
+                ..
+            })
+        ));
+
+        // Degenerate types do not panic.
+        for ty in [
+            ValueType::Array(Box::new(ValueType::Struct(vec![])), 2),
+            ValueType::Array(Box::new(ValueType::Uint(0)), 3),
+            ValueType::Struct(vec![ValueType::Uint(0), ValueType::Struct(vec![])]),
+        ] {
+            let repr = BinaryRepr::from_nodes(&ty, &[]).unwrap();
+            assert_eq!(repr.len(), 0);
+        }
+        assert!(matches!(
+            BinaryRepr::from_nodes(
+                &ValueType::Array(Box::new(ValueType::Struct(vec![])), 2),
+                &nodes[..1]
+            ),
+            Err(TypeError::InvalidLength {
+                expected: 0,
+                actual: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            BinaryRepr::from_nodes(&ValueType::Array(Box::new(ValueType::U8), 0), &[]),
+            Err(TypeError::ZeroLengthArray)
+        ));
+    }
+
+    #[test]
+    fn test_compact_value_limits() {
+        let max = format!("[U8(0)×{MAX_COMPACT_ELEMENTS}]");
+        assert_eq!(
+            Value::from_str_compact(&max).unwrap().bit_len(),
+            8 * MAX_COMPACT_ELEMENTS
+        );
+
+        for s in [
+            "[U128(1)×1000000000000]",
+            "[U8(0)×18446744073709551615]",
+            "[[U8(0)×1024]×1024]",
+        ] {
+            assert!(matches!(
+                s.parse::<CompactValue>(),
+                Err(TypeError::InvalidSyntax { .. })
+            ));
+        }
+
+        let deep = format!("{}U8(0){}", "[".repeat(200_000), "]".repeat(200_000));
+        assert!(matches!(
+            Value::from_str_compact(&deep),
+            Err(TypeError::InvalidSyntax { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compact_value() {
+        let zeros = Value::from([0u8; 1024]);
+        let compact = zeros.to_string_compact();
+        assert_eq!(compact, "[U8(0)×1024]");
+        assert_eq!(Value::from_str_compact(&compact).unwrap(), zeros);
+
+        let mixed = Value::Array(vec![
+            Value::from([1u16, 2, 2, 2, 1]),
+            Value::from([1u16, 2, 2, 2, 1]),
+            Value::from([7u16; 5]),
+        ]);
+        let compact = mixed.to_string_compact();
+        assert_eq!(compact, "[[U16(1), U16(2)×3, U16(1)]×2, [U16(7)×5]]");
+        assert_eq!(Value::from_str_compact(&compact).unwrap(), mixed);
+
+        let value = Value::Struct(vec![
+            Value::Bit(true),
+            Value::uint_from_le_bytes(12, &[0x01, 0x02]).unwrap(),
+            Value::from([u128::MAX; 3]),
+        ]);
+        let compact = value.to_string_compact();
+        assert_eq!(
+            compact,
+            format!("{{Bit(true), Uint<12>(0x0201), [U128({})×3]}}", u128::MAX)
+        );
+        assert_eq!(
+            compact.parse::<CompactValue>().unwrap(),
+            CompactValue(value.clone())
+        );
+        assert_eq!(CompactValue(value).to_string(), compact);
+
+        for invalid in [
+            "",
+            "[]",
+            "U8(256)",
+            "[U8(0)×0]",
+            "[U8(0), U16(0)]",
+            "Uint<12>(0x01)",
+            "U8(1) U8(2)",
+            "Named<>(U8(1))",
+            "Named<\"a>(U8(1))",
+            "Named<\"\\a\">(U8(1))",
+        ] {
+            assert!(
+                Value::from_str_compact(invalid).is_err(),
+                "{} should not parse",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_compact_value_names() {
+        let named = |name: &str| Value::Named {
+            name: name.to_string(),
+            inner: Box::new(Value::U8(1)),
+        };
+
+        let value = named("my_label");
+        assert_eq!(value.to_string_compact(), "Named<my_label>(U8(1))");
+        assert_eq!(
+            Value::from_str_compact("Named<my_label>(U8(1))").unwrap(),
+            value
+        );
+
+        for (name, compact) in [
+            ("my-label", r#"Named<"my-label">(U8(1))"#),
+            ("", r#"Named<"">(U8(1))"#),
+            (" a>b ", r#"Named<" a>b ">(U8(1))"#),
+            (r#"say "hi" \o/"#, r#"Named<"say \"hi\" \\o/">(U8(1))"#),
+        ] {
+            let value = named(name);
+            assert_eq!(value.to_string_compact(), compact);
+            assert_eq!(Value::from_str_compact(compact).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_display_with() {
+        let value = Value::Array((0..1000u16).map(Value::U16).collect());
+        let opts = DisplayOpts {
+            max_elements: Some(6),
+            ..Default::default()
+        };
+        assert_eq!(
+            value.display_with(opts),
+            "[U16(0), U16(1), U16(2), …, U16(997), U16(998), U16(999)]"
+        );
+
+        let value = Value::Struct(vec![Value::U8(10), Value::from([true, false])]);
+        let opts = DisplayOpts {
+            radix: Radix::Hex,
+            separator: "; ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            value.display_with(opts),
+            "{U8(0xa); [Bit(true); Bit(false)]}"
+        );
+
+        let opts = DisplayOpts {
+            radix: Radix::Binary,
+            ..Default::default()
+        };
+        assert_eq!(Value::U8(5).display_with(opts.clone()), "U8(0b101)");
+        assert_eq!(
+            Value::uint_from_le_bytes(4, &[0x05])
+                .unwrap()
+                .display_with(opts),
+            "Uint<4>(0b0101)"
+        );
+
+        let short = Value::from([1u8, 2]);
+        let opts = DisplayOpts {
+            max_elements: Some(6),
+            ..Default::default()
+        };
+        assert_eq!(short.display_with(opts), "[U8(1), U8(2)]");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_compact_value_serde() {
+        let value = CompactValue(Value::from([0u32; 256]));
+        let bytes = bincode::serialize(&value).unwrap();
+        assert_eq!(bincode::deserialize::<CompactValue>(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn test_scalar_bits() {
+        assert_eq!(ValueType::U32.scalar_bits(), Some(32));
+        assert_eq!(ValueType::Uint(12).scalar_bits(), Some(12));
+        assert_eq!(ValueType::new_array::<u8>(4).scalar_bits(), Some(8));
+        assert_eq!(
+            ValueType::Array(Box::new(ValueType::new_array::<u16>(4)), 3).scalar_bits(),
+            Some(16)
+        );
+        assert_eq!(
+            ValueType::Struct(vec![ValueType::U8, ValueType::U16]).scalar_bits(),
+            None
+        );
+
+        assert!(ValueType::Bit.is_scalar());
+        assert!(ValueType::Uint(12).is_scalar());
+        assert!(!ValueType::new_array::<u8>(4).is_scalar());
+        assert!(!ValueType::Struct(vec![ValueType::U8]).is_scalar());
+
+        assert!(ValueType::U64.is_integer());
+        assert!(ValueType::Uint(12).is_integer());
+        assert!(!ValueType::Bit.is_integer());
+        assert!(!ValueType::new_array::<u8>(4).is_integer());
+        assert!(Value::U16(1).is_integer());
+        assert!(!Value::Bit(true).is_integer());
+        assert!(!Value::from([1u8, 2]).is_integer());
+    }
+
+    #[test]
+    fn test_resize_array() {
+        let value = Value::from([1u8, 2]);
+
+        assert_eq!(
+            value.resize_array(4, Value::U8(0xff)).unwrap(),
+            Value::from([1u8, 2, 0xff, 0xff])
+        );
+        assert_eq!(
+            value.resize_array(1, Value::U8(0xff)).unwrap(),
+            Value::from([1u8])
+        );
+        assert_eq!(value.resize_array(2, Value::U8(0xff)).unwrap(), value);
+
+        assert!(matches!(
+            value.resize_array(4, Value::U16(0)),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            value.resize_array(0, Value::U8(0)),
+            Err(TypeError::ZeroLengthArray)
+        ));
+        assert!(matches!(
+            Value::U8(1).resize_array(4, Value::U8(0)),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_rotate_array() {
+        let value = Value::from([[0u8, 1], [2, 3], [4, 5], [6, 7]]);
+
+        assert_eq!(
+            value.rotate_array_left(1).unwrap(),
+            Value::from([[2u8, 3], [4, 5], [6, 7], [0, 1]])
+        );
+        assert_eq!(
+            value.rotate_array_right(1).unwrap(),
+            Value::from([[6u8, 7], [0, 1], [2, 3], [4, 5]])
+        );
+        assert_eq!(value.rotate_array_left(4).unwrap(), value);
+        assert_eq!(
+            value.rotate_array_left(5).unwrap(),
+            value.rotate_array_left(1).unwrap()
+        );
+        assert_eq!(
+            value.rotate_array_left(3).unwrap(),
+            value.rotate_array_right(1).unwrap()
+        );
+
+        assert!(matches!(
+            Value::U32(1).rotate_array_left(1),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_schema() {
+        use serde_json::json;
+
+        assert_eq!(
+            ValueType::U8.to_json_schema(),
+            json!({ "type": "integer", "minimum": 0, "maximum": 255 })
+        );
+        assert_eq!(
+            ValueType::new_array::<u8>(4).to_json_schema(),
+            json!({
+                "type": "array",
+                "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+                "minItems": 4,
+                "maxItems": 4,
+            })
+        );
+        assert_eq!(
+            ValueType::Bit.to_json_schema(),
+            json!({ "type": "boolean" })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_plain() {
+        use serde_json::json;
+
+        let value = Value::U32(0xdeadbeef);
+        assert_eq!(value.to_json_plain(), json!(0xdeadbeef_u32));
+        assert_eq!(
+            Value::from_json_plain(&ValueType::U32, &json!(0xdeadbeef_u32)).unwrap(),
+            value
+        );
+        assert!(Value::from_json_plain(&ValueType::U32, &json!(u32::MAX as u64 + 1)).is_err());
+        assert!(Value::from_json_plain(&ValueType::U32, &json!("1")).is_err());
+
+        let value = Value::U128(u128::MAX);
+        assert_eq!(value.to_json_plain(), json!(u128::MAX.to_string()));
+        assert_eq!(
+            Value::from_json_plain(&ValueType::U128, &value.to_json_plain()).unwrap(),
+            value
+        );
+        assert_eq!(
+            Value::from_json_plain(&ValueType::U128, &json!(42u64)).unwrap(),
+            Value::U128(42)
+        );
+
+        let value = Value::Array(vec![Value::from([1u8, 2]), Value::from([3u8, 4])]);
+        let ty = value.value_type();
+        assert_eq!(value.to_json_plain(), json!([[1u8, 2u8], [3u8, 4u8]]));
+        assert_eq!(
+            Value::from_json_plain(&ty, &json!([[1u8, 2u8], [3u8, 4u8]])).unwrap(),
+            value
+        );
+        assert!(Value::from_json_plain(&ty, &json!([[1u8, 2u8], [3u8, 256u16]])).is_err());
+        assert!(Value::from_json_plain(&ty, &json!([[1u8, 2u8]])).is_err());
+
+        let value = Value::uint_from_le_bytes(12, &[0x01, 0x02]).unwrap();
+        assert_eq!(value.to_json_plain(), json!("0x0201"));
+        assert_eq!(
+            Value::from_json_plain(&ValueType::Uint(12), &json!("0x0201")).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_array_element_error() {
+        let value = Value::Array(vec![
+            Value::U8(1),
+            Value::U8(2),
+            Value::U16(3),
+            Value::U8(4),
+        ]);
+
+        let err = <[u8; 4]>::try_from(value.clone()).unwrap_err();
+        assert!(matches!(
+            err,
+            TypeError::ArrayElement { index: 2, ref source }
+                if matches!(**source, TypeError::UnexpectedType { .. })
+        ));
+
+        let err = Vec::<u8>::try_from(value).unwrap_err();
+        assert!(matches!(err, TypeError::ArrayElement { index: 2, .. }));
+        assert!(err.to_string().contains("index 2"));
+    }
+
+    #[test]
+    fn test_bin_repr_reversed() {
+        let builder = CircuitBuilder::new();
+        let repr: BinaryRepr = builder.add_input::<u16>().into();
+
+        let nodes: Vec<_> = repr.iter().copied().collect();
+        let reversed = repr.reversed(false);
+        assert!(matches!(reversed, BinaryRepr::U16(_)));
+        assert!(reversed.iter().eq(nodes.iter().rev()));
+
+        let repr: BinaryRepr = builder.add_input::<[u8; 2]>().into();
+        let nodes: Vec<_> = repr.iter().copied().collect();
+
+        let reversed: Vec<_> = repr.reversed(false).iter().copied().collect();
+        let expected: Vec<_> = nodes
+            .chunks(8)
+            .flat_map(|chunk| chunk.iter().rev())
+            .copied()
+            .collect();
+        assert_eq!(reversed, expected);
+
+        let reversed: Vec<_> = repr.reversed(true).iter().copied().collect();
+        assert_eq!(reversed, nodes.into_iter().rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_bin_repr_disjoint_nodes() {
+        let builder = CircuitBuilder::new();
+        let a: BinaryRepr = builder.add_input::<u8>().into();
+        let b: BinaryRepr = builder.add_input::<u16>().into();
+
+        assert!(BinaryRepr::disjoint_nodes(&[&a, &b]).is_ok());
+        assert!(BinaryRepr::disjoint_nodes(&[]).is_ok());
+
+        let BinaryRepr::U16(U16(nodes)) = &b else {
+            unreachable!()
+        };
+        let overlapping = BinaryRepr::Uint(Uint(nodes[..4].to_vec()));
+        let ids: Vec<usize> = nodes[..4].iter().map(|node| node.id()).collect();
+
+        match BinaryRepr::disjoint_nodes(&[&a, &b, &overlapping]) {
+            Err(TypeError::NodeCollision { ids: collisions }) => assert_eq!(collisions, ids),
+            res => panic!("expected a collision, got {res:?}"),
+        }
+    }
+
+    #[cfg(all(feature = "tracing", not(feature = "rayon")))]
+    #[test]
+    fn test_from_bin_repr_tracing() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{
+            field::{Field, Visit},
+            span, Event, Metadata, Subscriber,
+        };
+
+        /// Records the index of each element decode span, in the order they are created.
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<u64>>>);
+
+        struct IndexVisitor(Option<u64>);
+
+        impl Visit for IndexVisitor {
+            fn record_u64(&mut self, field: &Field, value: u64) {
+                if field.name() == "index" {
+                    self.0 = Some(value);
+                }
+            }
+
+            fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+        }
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                let mut visitor = IndexVisitor(None);
+                span.record(&mut visitor);
+
+                let mut indices = self.0.lock().unwrap();
+                if span.metadata().name() == "decode_element" {
+                    indices.push(visitor.0.unwrap());
+                }
+                span::Id::from_u64(indices.len() as u64 + 1)
+            }
+
+            fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+            fn event(&self, _event: &Event<'_>) {}
+
+            fn enter(&self, _span: &span::Id) {}
+
+            fn exit(&self, _span: &span::Id) {}
+        }
+
+        let builder = CircuitBuilder::new();
+        let repr =
+            builder.add_input_by_type(ValueType::Array(Box::new(ValueType::new_array::<u8>(2)), 3));
+        let bits = vec![false; repr.len()];
+
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            repr.from_bin_repr(&bits).unwrap();
+        });
+
+        // Each outer element span is followed by the spans of its own elements.
+        assert_eq!(*recorder.0.lock().unwrap(), vec![0, 0, 1, 1, 0, 1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn test_bin_repr_to_value_type() {
+        let builder = CircuitBuilder::new();
+        let ty = ValueType::Array(
+            Box::new(ValueType::Array(
+                Box::new(ValueType::Struct(vec![ValueType::U8, ValueType::Uint(3)])),
+                2,
+            )),
+            3,
+        );
+        let repr = builder.add_input_by_type(ty.clone());
+
+        assert_eq!(repr.to_value_type(), repr.value_type());
+        assert_eq!(repr.to_value_type(), ty);
+
+        let empty = BinaryRepr::Array(Vec::new());
+        assert_eq!(
+            empty.to_value_type(),
+            ValueType::Array(Box::new(ValueType::Struct(Vec::new())), 0)
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&repr.to_value_type()).unwrap();
+            let decoded: ValueType = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, ty);
+        }
+    }
+
+    #[test]
+    fn test_input_offsets() {
+        assert_eq!(
+            input_offsets(&[ValueType::U8, ValueType::U32, ValueType::Bit]),
+            vec![0, 8, 40]
+        );
+        assert!(input_offsets(&[]).is_empty());
+
+        let builder = CircuitBuilder::new();
+        let types = [
+            ValueType::U16,
+            ValueType::new_array::<u8>(3),
+            ValueType::U64,
+        ];
+        for ty in &types {
+            builder.add_input_by_type(ty.clone());
+        }
+        let circ = builder.build().unwrap();
+
+        let offsets: Vec<_> = circ
+            .inputs()
+            .iter()
+            .map(|input| input.iter().next().unwrap().id())
+            .collect();
+        assert_eq!(offsets, input_offsets(&types));
+    }
+
+    #[test]
+    fn test_try_byte_chunks() {
+        let builder = CircuitBuilder::new();
+        let repr: BinaryRepr = builder.add_input::<u32>().into();
+
+        let chunks: Vec<_> = repr.try_byte_chunks().unwrap().collect();
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks
+            .iter()
+            .flatten()
+            .zip(repr.iter())
+            .all(|(a, b)| a == b));
+
+        let repr: BinaryRepr = builder.add_input::<bool>().into();
+        assert!(matches!(
+            repr.try_byte_chunks(),
+            Err(TypeError::InvalidLength {
+                expected: 8,
+                actual: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_splat() {
+        let value = Value::splat(Value::U8(42), 16).unwrap();
+        assert_eq!(value.value_type(), ValueType::new_array::<u8>(16));
+        assert_eq!(value, Value::from([42u8; 16]));
+
+        let nested = Value::splat(value, 2).unwrap();
+        assert_eq!(
+            nested.value_type(),
+            ValueType::Array(Box::new(ValueType::new_array::<u8>(16)), 2)
+        );
+
+        assert!(matches!(
+            Value::splat(Value::U8(42), 0),
+            Err(TypeError::ZeroLengthArray)
+        ));
+    }
+
+    #[test]
+    fn test_repeat_pattern() {
+        assert_eq!(
+            Value::repeat_pattern(&Value::U8(0xaa), &ValueType::U32).unwrap(),
+            Value::U32(0xaaaa_aaaa)
+        );
+        assert_eq!(
+            Value::repeat_pattern(&Value::Bit(true), &ValueType::U16).unwrap(),
+            Value::U16(u16::MAX)
+        );
+        assert_eq!(
+            Value::repeat_pattern(&Value::U16(0x1234), &ValueType::new_array::<u8>(4)).unwrap(),
+            Value::from([0x34u8, 0x12, 0x34, 0x12])
+        );
+        assert_eq!(
+            Value::repeat_pattern(&Value::U32(7), &ValueType::U32).unwrap(),
+            Value::U32(7)
+        );
+
+        assert!(matches!(
+            Value::repeat_pattern(&Value::U8(0xaa), &ValueType::Uint(12)),
+            Err(TypeError::InvalidLength {
+                expected: 16,
+                actual: 12,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Value::repeat_pattern(&Value::U64(1), &ValueType::U32),
+            Err(TypeError::InvalidLength { .. })
+        ));
+    }
+
+    #[test]
+    fn test_from_sparse() {
+        let value =
+            Value::from_sparse(8, Value::U16(0), [(2, Value::U16(7)), (6, Value::U16(9))]).unwrap();
+        assert_eq!(value, Value::from([0u16, 0, 7, 0, 0, 0, 9, 0]));
+
+        assert_eq!(
+            Value::from_sparse(2, Value::Bit(false), []).unwrap(),
+            Value::from([false, false])
+        );
+
+        assert!(matches!(
+            Value::from_sparse(8, Value::U16(0), [(8, Value::U16(1))]),
+            Err(TypeError::InvalidLength {
+                expected: 8,
+                actual: 9,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Value::from_sparse(8, Value::U16(0), [(usize::MAX, Value::U16(1))]),
+            Err(TypeError::InvalidLength {
+                expected: 8,
+                actual: usize::MAX,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Value::from_sparse(8, Value::U16(0), [(3, Value::U8(1))]),
+            Err(TypeError::ArrayElement { index: 3, .. })
+        ));
+        assert!(matches!(
+            Value::from_sparse(0, Value::U16(0), []),
+            Err(TypeError::ZeroLengthArray)
+        ));
+    }
+
+    #[test]
+    fn test_value_type_matches() {
+        let any = ValueType::Array(Box::new(ValueType::U8), 0);
+        let five = ValueType::new_array::<u8>(5);
+
+        assert!(any.matches(&five));
+        assert!(five.matches(&any));
+        assert_ne!(any, five);
+        assert!(!five.matches(&ValueType::new_array::<u8>(4)));
+        assert!(!any.matches(&ValueType::new_array::<u16>(5)));
+        assert!(!any.matches(&ValueType::U8));
+
+        let nested = ValueType::Array(Box::new(any.clone()), 2);
+        assert!(nested.matches(&ValueType::Array(Box::new(five), 2)));
+        assert_eq!(any.to_string(), "Array<U8, _>");
+
+        let err = Vec::<u8>::try_from(Value::from([1u16, 2])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unexpected type, expected: Array<U8, _>, actual: Array<U16, 2>"
+        );
+        assert_eq!(
+            Vec::<u8>::try_from(Value::from([1u8, 2])).unwrap(),
+            vec![1, 2]
+        );
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn test_rkyv_binary_repr() {
+        use rkyv::Deserialize;
+
+        let builder = CircuitBuilder::new();
+        let a: BinaryRepr = builder.add_input::<[u8; 2]>().into();
+        let b: BinaryRepr = builder.add_input::<[u8; 2]>().into();
+        let repr = BinaryRepr::Array(vec![a, b]);
+
+        let bytes = rkyv::to_bytes::<_, 256>(&repr).unwrap();
+        let archived = rkyv::check_archived_root::<BinaryRepr>(&bytes).unwrap();
+
+        // The nested arrays can be accessed without deserializing.
+        let ArchivedBinaryRepr::Array(elems) = archived else {
+            panic!("expected an array");
+        };
+        assert_eq!(elems.len(), 2);
+        assert!(matches!(&elems[1], ArchivedBinaryRepr::Array(v) if v.len() == 2));
+
+        let deserialized: BinaryRepr = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized.value_type(), repr.value_type());
+        assert!(deserialized.iter().eq(repr.iter()));
+
+        let ty = repr.value_type();
+        let bytes = rkyv::to_bytes::<_, 256>(&ty).unwrap();
+        let archived = rkyv::check_archived_root::<ValueType>(&bytes).unwrap();
+        let deserialized: ValueType = archived.deserialize(&mut rkyv::Infallible).unwrap();
+        assert_eq!(deserialized, ty);
+    }
+
+    #[test]
+    fn test_min_max() {
+        assert_eq!(Value::U8(3).min(&Value::U8(200)).unwrap(), Value::U8(3));
+        assert_eq!(Value::U8(3).max(&Value::U8(200)).unwrap(), Value::U8(200));
+        assert_eq!(
+            Value::U128(u128::MAX).min(&Value::U128(0)).unwrap(),
+            Value::U128(0)
+        );
+
+        let a = Value::uint_from_le_bytes(12, &[0xff, 0x01]).unwrap();
+        let b = Value::uint_from_le_bytes(12, &[0x00, 0x02]).unwrap();
+        assert_eq!(a.min(&b).unwrap(), a);
+        assert_eq!(a.max(&b).unwrap(), b);
+
+        assert_eq!(
+            Value::from([1u16, 50, 7])
+                .min(&Value::from([2u16, 40, 7]))
+                .unwrap(),
+            Value::from([1u16, 40, 7])
+        );
+
+        assert!(matches!(
+            Value::Bit(true).min(&Value::Bit(false)),
+            Err(TypeError::NotInteger { .. })
+        ));
+        assert!(matches!(
+            Value::U8(1).max(&Value::U16(1)),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(Value::from([1u8, 2]).min(&Value::from([1u8])).is_err());
+    }
+
+    #[test]
+    fn test_first_diff_bit() {
+        let a = Value::U128(1 << 100);
+        let b = Value::U128((1 << 100) | (1 << 37));
+        assert_eq!(a.first_diff_bit(&b).unwrap(), Some(37));
+        assert_eq!(a.first_diff_bit(&Value::U128(0)).unwrap(), Some(100));
+
+        let nested = Value::Array(vec![Value::from([1u8, 2]), Value::from([3u8, 4])]);
+        assert_eq!(nested.first_diff_bit(&nested.clone()).unwrap(), None);
+
+        let other = Value::Array(vec![Value::from([1u8, 2]), Value::from([3u8, 6])]);
+        assert_eq!(nested.first_diff_bit(&other).unwrap(), Some(3 * 8 + 1));
+
+        assert!(matches!(
+            a.first_diff_bit(&Value::U64(0)),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        assert_eq!(
+            Value::U32(0b1011).hamming_distance(&Value::U32(0)).unwrap(),
+            3
+        );
+        assert_eq!(
+            Value::U8(0xff).hamming_distance(&Value::U8(0xff)).unwrap(),
+            0
+        );
+        assert_eq!(
+            Value::Bit(true)
+                .hamming_distance(&Value::Bit(false))
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            Value::U128(u128::MAX)
+                .hamming_distance(&Value::U128(0))
+                .unwrap(),
+            128
+        );
+
+        let a = Value::uint_from_le_bytes(24, &[0x01, 0x02, 0x03]).unwrap();
+        let b = Value::uint_from_le_bytes(24, &[0x00, 0x02, 0x00]).unwrap();
+        assert_eq!(a.hamming_distance(&b).unwrap(), 3);
+
+        let nested = Value::Array(vec![Value::from([1u8, 2]), Value::from([3u8, 4])]);
+        let other = Value::Array(vec![Value::from([0u8, 2]), Value::from([3u8, 0xf4])]);
+        assert_eq!(nested.hamming_distance(&other).unwrap(), 5);
+
+        assert!(matches!(
+            nested.hamming_distance(&Value::U64(0)),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_reinterpret() {
+        let value = Value::U32(0x12345678);
+
+        let bytes = value.reinterpret(&ValueType::new_array::<u8>(4)).unwrap();
+        assert_eq!(bytes, Value::from([0x78u8, 0x56, 0x34, 0x12]));
+        assert_eq!(bytes.reinterpret(&ValueType::U32).unwrap(), value);
+
+        let halves = value.reinterpret(&ValueType::new_array::<u16>(2)).unwrap();
+        assert_eq!(halves, Value::from([0x5678u16, 0x1234]));
+        assert_eq!(halves.reinterpret(&ValueType::U32).unwrap(), value);
+
+        let bits: Vec<bool> = value.clone().into_iter_lsb0().collect();
+        for ty in [
+            ValueType::new_array::<bool>(32),
+            ValueType::Uint(32),
+            ValueType::Struct(vec![ValueType::U8, ValueType::Uint(24)]),
+        ] {
+            let reinterpreted = value.reinterpret(&ty).unwrap();
+            assert_eq!(reinterpreted.value_type(), ty);
+            assert_eq!(reinterpreted.into_iter_lsb0().collect::<Vec<_>>(), bits);
+        }
+
+        assert!(matches!(
+            value.reinterpret(&ValueType::U64),
+            Err(TypeError::InvalidLength {
+                expected: 64,
+                actual: 32,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_convert_bytes_endian() {
+        for (endian, expected) in [
+            (Endian::Big, 0x12345678u32.to_be_bytes()),
+            (Endian::Little, 0x12345678u32.to_le_bytes()),
+        ] {
+            let builder = CircuitBuilder::new();
+            let a = builder.add_input::<u32>();
+            builder.add_output(a.to_bytes(endian));
+            let circ = builder.build().unwrap();
+
+            let output = circ.evaluate(&[Value::U32(0x12345678)]).unwrap();
+            assert_eq!(output, vec![Value::from(expected)]);
+
+            let builder = CircuitBuilder::new();
+            let bytes = builder
+                .add_input::<[u8; 4]>()
+                .to_inner()
+                .map(|byte| Tracer::new(builder.state(), byte));
+            builder.add_output(Tracer::<U32>::from_bytes(bytes, endian));
+            let circ = builder.build().unwrap();
+
+            let output = circ.evaluate(&[Value::from(expected)]).unwrap();
+            assert_eq!(output, vec![Value::U32(0x12345678)]);
+        }
+    }
+
+    #[test]
+    fn test_is_zero_is_all_ones() {
+        for value in [
+            Value::Bit(false),
+            Value::U8(0),
+            Value::U128(0),
+            Value::Uint(vec![false; 12]),
+            Value::Array(vec![Value::from([0u16; 3]); 2]),
+        ] {
+            assert!(value.is_zero(), "{} should be zero", value);
+            assert!(!value.is_all_ones(), "{} should not be all ones", value);
+        }
+
+        for value in [
+            Value::Bit(true),
+            Value::U8(u8::MAX),
+            Value::U128(u128::MAX),
+            Value::Uint(vec![true; 12]),
+            Value::Array(vec![Value::from([u16::MAX; 3]); 2]),
+        ] {
+            assert!(value.is_all_ones(), "{} should be all ones", value);
+            assert!(!value.is_zero(), "{} should not be zero", value);
+        }
+
+        for value in [
+            Value::U8(1),
+            Value::U32(0x0000ffff),
+            Value::from([0u8, u8::MAX]),
+            Value::Struct(vec![Value::Bit(true), Value::U8(0)]),
+        ] {
+            assert!(!value.is_zero(), "{} should not be zero", value);
+            assert!(!value.is_all_ones(), "{} should not be all ones", value);
+        }
+    }
+
+    #[test]
+    fn test_value_type_visitor() {
+        #[derive(Default)]
+        struct Recorder(Vec<String>);
+
+        impl ValueTypeVisitor for Recorder {
+            fn visit_scalar(&mut self, ty: &ValueType) {
+                self.0.push(ty.to_string());
+            }
+
+            fn enter_array(&mut self, _elem: &ValueType, len: usize) {
+                self.0.push(format!("enter_array({})", len));
+            }
+
+            fn exit_array(&mut self, _elem: &ValueType, len: usize) {
+                self.0.push(format!("exit_array({})", len));
+            }
+
+            fn enter_struct(&mut self, fields: &[ValueType]) {
+                self.0.push(format!("enter_struct({})", fields.len()));
+            }
+
+            fn exit_struct(&mut self, fields: &[ValueType]) {
+                self.0.push(format!("exit_struct({})", fields.len()));
+            }
+        }
+
+        let ty = ValueType::Struct(vec![
+            ValueType::Bit,
+            ValueType::Array(Box::new(ValueType::new_array::<u8>(2)), 2),
+        ]);
+
+        let mut recorder = Recorder::default();
+        ty.accept(&mut recorder);
+
+        assert_eq!(
+            recorder.0,
+            [
+                "enter_struct(2)",
+                "Bit",
+                "enter_array(2)",
+                "enter_array(2)",
+                "U8",
+                "U8",
+                "exit_array(2)",
+                "enter_array(2)",
+                "U8",
+                "U8",
+                "exit_array(2)",
+                "exit_array(2)",
+                "exit_struct(2)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(
+            Value::from_str(&ValueType::U8, "0xFF").unwrap(),
+            Value::U8(255)
+        );
+        assert_eq!(
+            Value::from_str(&ValueType::U8, "0b101").unwrap(),
+            Value::U8(5)
+        );
+        assert_eq!(
+            Value::from_str(&ValueType::U128, &u128::MAX.to_string()).unwrap(),
+            Value::U128(u128::MAX)
+        );
+        assert_eq!(
+            Value::from_str(&ValueType::Uint(12), "0xFFF").unwrap(),
+            Value::Uint(vec![true; 12])
+        );
+        assert_eq!(
+            Value::from_str(&ValueType::Bit, "true").unwrap(),
+            Value::Bit(true)
+        );
+
+        assert_eq!(
+            Value::from_str(&ValueType::new_array::<u8>(3), "[1, 2, 3]").unwrap(),
+            Value::from([1u8, 2, 3])
+        );
+        let ty = ValueType::Struct(vec![
+            ValueType::U16,
+            ValueType::Array(Box::new(ValueType::new_array::<u8>(2)), 2),
+        ]);
+        assert_eq!(
+            Value::from_str(&ty, " { 0x100, [[1, 2], [0b11, 4]] } ").unwrap(),
+            Value::Struct(vec![
+                Value::U16(256),
+                Value::Array(vec![Value::from([1u8, 2]), Value::from([3u8, 4])]),
+            ])
+        );
+
+        // There are no signed types, so negative values are rejected.
+        let err = Value::from_str(&ValueType::U8, "-1").unwrap_err();
+        assert!(err.to_string().contains("negative"));
+
+        for (ty, s) in [
+            (ValueType::U8, "256"),
+            (ValueType::U8, "0x100"),
+            (ValueType::U8, "0b111111111"),
+            (ValueType::Uint(12), "0x1FFF"),
+            (ValueType::U8, "0x"),
+            (ValueType::U8, "0xGG"),
+            (ValueType::Bit, "2"),
+            (ValueType::new_array::<u8>(3), "[1, 2]"),
+            (ValueType::new_array::<u8>(3), "1, 2, 3"),
+        ] {
+            assert!(
+                matches!(
+                    Value::from_str(&ty, s),
+                    Err(TypeError::InvalidSyntax { .. })
+                ),
+                "{:?} should not parse as {}",
+                s,
+                ty
+            );
+        }
+
+        let err = Value::from_str(&ValueType::new_array::<u8>(3), "[1, 2, 300]").unwrap_err();
+        assert!(matches!(err, TypeError::InvalidSyntax { position: 7, .. }));
+    }
+
+    #[test]
+    fn test_extract_bits() {
+        let value = Value::U16(0xabcd);
+
+        assert_eq!(
+            value.extract_bits(8, 8, &ValueType::U8).unwrap(),
+            Value::U8(0xab)
+        );
+        assert_eq!(
+            value.extract_bits(4, 4, &ValueType::Uint(4)).unwrap(),
+            Value::Uint(vec![false, false, true, true])
+        );
+
+        let array = Value::from([0x12u8, 0x34]);
+        assert_eq!(
+            array.extract_bits(4, 8, &ValueType::U8).unwrap(),
+            Value::U8(0x41)
+        );
+
+        assert!(matches!(
+            value.extract_bits(9, 8, &ValueType::U8),
+            Err(TypeError::InvalidLength {
+                expected: 16,
+                actual: 17,
+                ..
+            })
+        ));
+        assert!(matches!(
+            value.extract_bits(0, 4, &ValueType::U8),
+            Err(TypeError::InvalidLength { .. })
+        ));
+    }
+
+    #[test]
+    fn test_add_with_carry() {
+        assert_eq!(
+            Value::U8(254).add_with_carry(&Value::U8(1), false).unwrap(),
+            (Value::U8(255), false)
+        );
+        assert_eq!(
+            Value::U8(254).add_with_carry(&Value::U8(1), true).unwrap(),
+            (Value::U8(0), true)
+        );
+        assert_eq!(
+            Value::U8(255)
+                .add_with_carry(&Value::U8(255), true)
+                .unwrap(),
+            (Value::U8(255), true)
+        );
+        assert_eq!(
+            Value::U8(0).add_with_carry(&Value::U8(0), true).unwrap(),
+            (Value::U8(1), false)
+        );
+
+        let a = Value::uint_from_le_bytes(12, &[0xff, 0x0f]).unwrap();
+        let b = Value::uint_from_le_bytes(12, &[0x00, 0x00]).unwrap();
+        assert_eq!(a.add_with_carry(&b, true).unwrap(), (b.clone(), true));
+        assert!(!b.add_with_carry(&b, true).unwrap().1);
+
+        assert!(matches!(
+            Value::U8(1).add_with_carry(&Value::U16(1), false),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::from([1u8]).add_with_carry(&Value::from([1u8]), false),
+            Err(TypeError::NotInteger { .. })
+        ));
+    }
+
+    #[test]
+    fn test_checked_div_rem() {
+        assert_eq!(
+            Value::U8(200).checked_div(&Value::U8(7)).unwrap(),
+            Value::U8(28)
+        );
+        assert_eq!(
+            Value::U8(200).checked_rem(&Value::U8(7)).unwrap(),
+            Value::U8(4)
+        );
+        assert_eq!(
+            Value::U128(u128::MAX).checked_div(&Value::U128(3)).unwrap(),
+            Value::U128(u128::MAX / 3)
+        );
+
+        let a = Value::uint_from_le_bytes(12, &[0xff, 0x0f]).unwrap();
+        let b = Value::uint_from_le_bytes(12, &[0x0a, 0x00]).unwrap();
+        assert_eq!(
+            a.checked_div(&b).unwrap(),
+            Value::uint_from_le_bytes(12, &[0x99, 0x01]).unwrap()
+        );
+        assert_eq!(
+            a.checked_rem(&b).unwrap(),
+            Value::uint_from_le_bytes(12, &[0x05, 0x00]).unwrap()
+        );
+
+        assert!(matches!(
+            Value::U32(1).checked_div(&Value::U32(0)),
+            Err(TypeError::DivisionByZero)
+        ));
+        assert!(matches!(
+            Value::U32(1).checked_rem(&Value::U16(1)),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::Bit(true).checked_div(&Value::Bit(true)),
+            Err(TypeError::NotInteger { .. })
+        ));
+    }
+
+    #[test]
+    fn test_segment() {
+        let value = Value::U64(0x0123_4567_89ab_cdef);
+
+        assert_eq!(
+            value.segment(&ValueType::U16).unwrap(),
+            Value::from([0x0123u16, 0x4567, 0x89ab, 0xcdef])
+        );
+        assert_eq!(
+            value.segment(&ValueType::U8).unwrap(),
+            value.to_byte_array().unwrap()
+        );
+        assert_eq!(
+            Value::U128(u128::MAX).segment(&ValueType::U32).unwrap(),
+            Value::from([u32::MAX; 4])
+        );
+        assert_eq!(
+            Value::U16(0xabcd).segment(&ValueType::Uint(4)).unwrap(),
+            Value::Array(
+                [0xa, 0xb, 0xc, 0xd]
+                    .iter()
+                    .map(|v| Value::from_u128(&ValueType::Uint(4), *v).unwrap())
+                    .collect()
+            )
+        );
+
+        assert!(matches!(
+            value.segment(&ValueType::Uint(24)),
+            Err(TypeError::InvalidLength {
+                expected: 72,
+                actual: 64,
+                ..
+            })
+        ));
+        assert!(matches!(
+            value.segment(&ValueType::Bit),
+            Err(TypeError::NotInteger { .. })
+        ));
+        assert!(matches!(
+            Value::from([1u8, 2]).segment(&ValueType::U8),
+            Err(TypeError::NotInteger { .. })
+        ));
+    }
+
+    #[test]
+    fn test_join_segments() {
+        let array = Value::from([0x0123u16, 0x4567, 0x89ab, 0xcdef]);
+
+        assert_eq!(
+            Value::join_segments(&array, &ValueType::U64).unwrap(),
+            Value::U64(0x0123_4567_89ab_cdef)
+        );
+
+        let value = Value::U128(0x0011_2233_4455_6677_8899_aabb_ccdd_eeff);
+        for chunk in [ValueType::U8, ValueType::U32, ValueType::Uint(4)] {
+            let segments = value.segment(&chunk).unwrap();
+            assert_eq!(
+                Value::join_segments(&segments, &ValueType::U128).unwrap(),
+                value
+            );
+        }
+
+        assert!(matches!(
+            Value::join_segments(&array, &ValueType::U32),
+            Err(TypeError::InvalidLength {
+                expected: 32,
+                actual: 64,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Value::join_segments(&Value::U64(1), &ValueType::U64),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::join_segments(&Value::from([true; 8]), &ValueType::U8),
+            Err(TypeError::ArrayElement { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_widening_mul() {
+        assert_eq!(
+            Value::U8(200).widening_mul(&Value::U8(3)).unwrap(),
+            Value::U16(600)
+        );
+        assert_eq!(
+            Value::U8(255).widening_mul(&Value::U8(255)).unwrap(),
+            Value::U16(65025)
+        );
+        assert_eq!(
+            Value::U64(u64::MAX)
+                .widening_mul(&Value::U64(u64::MAX))
+                .unwrap(),
+            Value::U128(u64::MAX as u128 * u64::MAX as u128)
+        );
+
+        let a = Value::uint_from_le_bytes(12, &[0xff, 0x0f]).unwrap();
+        let b = Value::uint_from_le_bytes(12, &[0x03, 0x00]).unwrap();
+        assert_eq!(
+            a.widening_mul(&b).unwrap(),
+            Value::uint_from_le_bytes(24, &[0xfd, 0x2f, 0x00]).unwrap()
+        );
+
+        assert!(matches!(
+            Value::U128(1).widening_mul(&Value::U128(1)),
+            Err(TypeError::NoWiderType {
+                ty: ValueType::U128
+            })
+        ));
+        assert_eq!(
+            Value::U128(1)
+                .widening_mul(&Value::U128(1))
+                .unwrap_err()
+                .to_string(),
+            "No integer type of twice the width of U128 exists"
+        );
+        assert!(matches!(
+            Value::U8(1).widening_mul(&Value::U16(1)),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::Bit(true).widening_mul(&Value::Bit(true)),
+            Err(TypeError::NotInteger { .. })
+        ));
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = Value::Array(vec![
+            Value::from([1u8, 2, 3]),
+            Value::from([4u8, 5, 6]),
+            Value::from([7u8, 8, 9]),
+        ]);
+        let b = Value::Array(vec![
+            Value::from([1u8, 2, 3]),
+            Value::from([4u8, 50, 6]),
+            Value::from([7u8, 8, 90]),
+        ]);
+
+        let diff = a.diff(&b).unwrap();
+        assert_eq!(
+            diff,
+            ValueDiff::Array(vec![
+                (
+                    1,
+                    ValueDiff::Array(vec![(
+                        1,
+                        ValueDiff::Scalar {
+                            left: Value::U8(5),
+                            right: Value::U8(50)
+                        }
+                    )])
+                ),
+                (
+                    2,
+                    ValueDiff::Array(vec![(
+                        2,
+                        ValueDiff::Scalar {
+                            left: Value::U8(9),
+                            right: Value::U8(90)
+                        }
+                    )])
+                ),
+            ])
+        );
+        assert_eq!(
+            diff.to_string(),
+            "[1][1]: U8(5) != U8(50)\n[2][2]: U8(9) != U8(90)\n"
+        );
+
+        assert!(a.diff(&a.clone()).unwrap().is_equal());
+        assert_eq!(
+            Value::U8(1).diff(&Value::U8(2)).unwrap().to_string(),
+            "value: U8(1) != U8(2)\n"
+        );
+
+        let s = Value::Struct(vec![Value::Bit(true), Value::from([1u16, 2])]);
+        let t = Value::Struct(vec![Value::Bit(true), Value::from([1u16, 3])]);
+        assert_eq!(s.diff(&t).unwrap().to_string(), ".1[1]: U16(2) != U16(3)\n");
+
+        assert!(a.diff(&Value::from([1u8, 2, 3])).is_err());
+    }
+
+    #[test]
+    fn test_to_bytes_exact() {
+        let value = Value::U32(0x1234_5678);
+
+        assert_eq!(
+            value.to_bytes_exact(BitOrder::Msb0).unwrap(),
+            0x1234_5678u32.to_be_bytes()
+        );
+        assert_eq!(
+            value.to_bytes_exact(BitOrder::Lsb0).unwrap(),
+            0x1234_5678u32.to_le_bytes()
+        );
+
+        assert!(matches!(
+            Value::from([true; 9]).to_bytes_exact(BitOrder::Lsb0),
+            Err(TypeError::NotByteAligned { bit_len: 9 })
+        ));
+    }
+
+    #[test]
+    fn test_xor_bits() {
+        let value = Value::U32(0x1234_5678);
+
+        let mut mask = vec![false; 32];
+        mask[0] = true;
+        mask[31] = true;
+        assert_eq!(
+            value.xor_bits(&mask, BitOrder::Lsb0).unwrap(),
+            Value::U32(0x9234_5679)
+        );
+
+        let mut mask = vec![false; 32];
+        mask[0] = true;
+        mask[1] = true;
+        assert_eq!(
+            value.xor_bits(&mask, BitOrder::Msb0).unwrap(),
+            Value::U32(0xd234_5678)
+        );
+
+        assert!(matches!(
+            value.xor_bits(&[true; 31], BitOrder::Lsb0),
+            Err(TypeError::InvalidLength {
+                expected: 32,
+                actual: 31,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_set_bit_indices() {
+        let value = Value::U16(0b1000_0000_0010_0101);
+
+        let lsb0 = value.set_bit_indices(BitOrder::Lsb0);
+        assert_eq!(lsb0, vec![0, 2, 5, 15]);
+        assert_eq!(
+            Value::from_set_indices(&ValueType::U16, lsb0, BitOrder::Lsb0).unwrap(),
+            value
+        );
+
+        let msb0 = value.set_bit_indices(BitOrder::Msb0);
+        assert_eq!(msb0, vec![0, 10, 13, 15]);
+        assert_eq!(
+            Value::from_set_indices(&ValueType::U16, msb0, BitOrder::Msb0).unwrap(),
+            value
+        );
+
+        assert!(matches!(
+            Value::from_set_indices(&ValueType::U16, [16], BitOrder::Lsb0),
+            Err(TypeError::InvalidLength {
+                expected: 16,
+                actual: 17,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Value::from_set_indices(&ValueType::U16, [usize::MAX], BitOrder::Lsb0),
+            Err(TypeError::InvalidLength {
+                expected: 16,
+                actual: usize::MAX,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_select() {
+        let (a, b) = (Value::U8(1), Value::U8(2));
+
+        assert_eq!(Value::select(&Value::Bit(true), &a, &b).unwrap(), a);
+        assert_eq!(Value::select(&Value::Bit(false), &a, &b).unwrap(), b);
+
+        assert!(matches!(
+            Value::select(&Value::U8(1), &a, &b),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::select(&Value::Bit(true), &a, &Value::U16(2)),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_select_bits() {
+        let (a, b) = (Value::U8(0b1010_1010), Value::U8(0b0101_0101));
+
+        assert_eq!(
+            Value::select_bits(&Value::U8(0b1111_0000), &a, &b).unwrap(),
+            Value::U8(0b1010_0101)
+        );
+        assert_eq!(Value::select_bits(&Value::U8(0xff), &a, &b).unwrap(), a);
+        assert_eq!(
+            Value::select_bits(&Value::from([true; 8]), &a, &b).unwrap(),
+            a
+        );
+
+        assert!(matches!(
+            Value::select_bits(&Value::U16(0), &a, &b),
+            Err(TypeError::InvalidLength {
+                expected: 8,
+                actual: 16,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_bit_matrix() {
+        let value = Value::from([0x01u8, 0x80, 0xa5]);
+
+        let matrix = value.to_bit_matrix(BitOrder::Msb0).unwrap();
+        assert_eq!(matrix.len(), 3);
+        assert!(matrix.iter().all(|row| row.len() == 8));
+        assert_eq!(
+            matrix[0],
+            [false, false, false, false, false, false, false, true]
+        );
+        assert_eq!(
+            matrix[1],
+            [true, false, false, false, false, false, false, false]
+        );
+        assert_eq!(
+            Value::from_bit_matrix(&matrix, &ValueType::U8, BitOrder::Msb0).unwrap(),
+            value
+        );
+
+        let matrix = value.to_bit_matrix(BitOrder::Lsb0).unwrap();
+        assert!(matrix[0][0]);
+        assert_eq!(
+            Value::from_bit_matrix(&matrix, &ValueType::U8, BitOrder::Lsb0).unwrap(),
+            value
+        );
+
+        assert!(Value::U8(1).to_bit_matrix(BitOrder::Lsb0).is_err());
+        assert!(matches!(
+            Value::from_bit_matrix(
+                &[vec![true; 8], vec![true; 7]],
+                &ValueType::U8,
+                BitOrder::Lsb0
+            ),
+            Err(TypeError::ArrayElement { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_bin_repr_zero_width_elements() {
+        let builder = CircuitBuilder::new();
+        let ty = ValueType::Array(Box::new(ValueType::Struct(vec![])), 2);
+
+        let repr = builder.add_input_by_type(ty.clone());
+        assert_eq!(repr.len(), 0);
+        assert_eq!(
+            repr.from_bin_repr(&[]).unwrap(),
+            Value::Array(vec![Value::Struct(vec![]); 2])
+        );
+
+        let ty = ValueType::Struct(vec![
+            ValueType::Array(Box::new(ValueType::Uint(0)), 3),
+            ValueType::U8,
+        ]);
+        let repr = builder.add_input_by_type(ty.clone());
+        let value = Value::Struct(vec![
+            Value::Array(vec![Value::Uint(vec![]); 3]),
+            Value::U8(5),
+        ]);
+        let bits: Vec<bool> = value.clone().into_iter_lsb0().collect();
+        assert_eq!(repr.from_bin_repr(&bits).unwrap(), value);
+
+        assert!(matches!(
+            BinaryRepr::Array(vec![]).from_bin_repr(&[]),
+            Err(TypeError::ZeroLengthArray)
+        ));
+    }
+
+    #[test]
+    fn test_from_bits_zero_width_elements() {
+        let ty = ValueType::Array(Box::new(ValueType::Uint(0)), 3);
+
+        assert_eq!(
+            Value::from_bits(&ty, [], BitOrder::Lsb0).unwrap(),
+            Value::Array(vec![Value::Uint(Vec::new()); 3])
+        );
+
+        let ty = ValueType::Struct(vec![
+            ValueType::Array(Box::new(ValueType::Struct(Vec::new())), 2),
+            ValueType::Bit,
+        ]);
+        assert_eq!(
+            Value::from_bits(&ty, [true], BitOrder::Lsb0).unwrap(),
+            Value::Struct(vec![
+                Value::Array(vec![Value::Struct(Vec::new()); 2]),
+                Value::Bit(true),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_from_bits() {
+        let ty = ValueType::Struct(vec![ValueType::U16, ValueType::new_array::<u8>(2)]);
+        let value = Value::Struct(vec![Value::U16(0x1234), Value::from([0x56u8, 0x78])]);
+
+        let lsb0: Vec<bool> = value.clone().into_iter_lsb0().collect();
+        assert_eq!(Value::from_bits(&ty, lsb0, BitOrder::Lsb0).unwrap(), value);
+
+        let msb0: Vec<bool> = value.clone().into_iter_msb0().collect();
+        assert_eq!(
+            Value::from_bits(&ty, msb0.iter().copied(), BitOrder::Msb0).unwrap(),
+            value
+        );
+
+        assert!(matches!(
+            Value::from_bits(&ty, msb0.iter().copied().take(31), BitOrder::Msb0),
+            Err(TypeError::InvalidLength {
+                expected: 32,
+                actual: 31,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Value::from_bits(&ty, std::iter::repeat_n(true, 40), BitOrder::Lsb0),
+            Err(TypeError::InvalidLength {
+                expected: 32,
+                actual: 40,
+                ..
+            })
+        ));
+    }
+
+    const _: () = assert!(<[[u8; 4]; 2]>::LEN == 64);
+    const _: () = assert!(<[[[u16; 3]; 2]; 5]>::LEN == 16 * 3 * 2 * 5);
+    const _: () = assert!(<[[bool; 7]; 3]>::LEN == 21);
+
+    #[test]
+    fn test_nested_array_binary_length() {
+        assert_eq!(<[[u8; 4]; 2]>::LEN, <[u8; 8]>::LEN);
+        assert_eq!(<[[[u32; 2]; 2]; 2]>::LEN, 256);
+    }
+
+    #[test]
+    fn test_nested_array_value_type() {
+        assert_eq!(
+            ValueType::new::<[[u8; 2]; 3]>(),
+            ValueType::Array(Box::new(ValueType::Array(Box::new(ValueType::U8), 2)), 3)
+        );
+        assert_eq!(
+            ValueType::new::<[[[bool; 1]; 2]; 3]>(),
+            ValueType::Array(
+                Box::new(ValueType::Array(
+                    Box::new(ValueType::new_array::<bool>(1)),
+                    2
+                )),
+                3
+            )
+        );
+
+        let value = Value::from([[1u8, 2], [3, 4], [5, 6]]);
+        assert_eq!(value.value_type(), ValueType::new::<[[u8; 2]; 3]>());
+    }
+
+    #[test]
+    fn test_nested_array_bin_repr() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<[[u8; 4]; 2]>();
+        let a = a.to_inner();
+
+        assert_eq!(a.len(), 2);
+        assert_eq!(a[0].len(), 4);
+
+        builder.add_output(a);
+        let circ = builder.build().unwrap();
+
+        let input = &circ.inputs()[0];
+        assert_eq!(input.len(), 64);
+        assert_eq!(input.value_type(), ValueType::new::<[[u8; 4]; 2]>());
+        assert_eq!(circ.outputs()[0].iter().count(), 64);
+
+        let nodes = input.iter().copied().collect::<Vec<_>>();
+        assert!(<[[u8; 4]; 2]>::new_bin_repr(&nodes).is_ok());
+        for len in [7, 10, 48, 62] {
+            assert!(matches!(
+                <[[u8; 4]; 2]>::new_bin_repr(&nodes[..len]),
+                Err(TypeError::InvalidLength { expected: 64, actual, .. }) if actual == len
+            ));
+        }
+
+        assert!(<[u8; 0]>::new_bin_repr(&[]).is_ok());
+        assert!(matches!(
+            <[u8; 0]>::new_bin_repr(&nodes[..1]),
+            Err(TypeError::InvalidLength {
+                expected: 0,
+                actual: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_type_code_round_trip() {
+        let types = [
+            ValueType::Bit,
+            ValueType::U8,
+            ValueType::U128,
+            ValueType::Uint(24),
+            ValueType::new::<[u32; 4]>(),
+            ValueType::new::<[[u8; 2]; 3]>(),
+            ValueType::Struct(vec![ValueType::U16, ValueType::new::<[bool; 5]>()]),
+        ];
+
+        for ty in types {
+            let code = ty.type_code();
+            assert_eq!(ValueType::from_type_code(&code).unwrap(), (ty, code.len()));
+        }
+
+        assert_eq!(ValueType::U8.type_code(), vec![0x01]);
+        assert_eq!(
+            ValueType::from_type_code(&[0x03, 0xff]).unwrap(),
+            (ValueType::U32, 1)
+        );
+    }
+
+    #[test]
+    fn test_type_code_invalid() {
+        assert!(matches!(
+            ValueType::from_type_code(&[]),
+            Err(TypeError::InvalidTypeCode { position: 0, .. })
+        ));
+        assert!(matches!(
+            ValueType::from_type_code(&[0xff]),
+            Err(TypeError::InvalidTypeCode { position: 0, .. })
+        ));
+
+        let mut code = ValueType::new::<[u8; 4]>().type_code();
+        code.pop();
+        assert!(matches!(
+            ValueType::from_type_code(&code),
+            Err(TypeError::InvalidTypeCode { position: 9, .. })
+        ));
+
+        let mut code = [0x09].to_vec();
+        code.extend_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            ValueType::from_type_code(&code),
+            Err(TypeError::InvalidTypeCode { position: 9, .. })
+        ));
+    }
+
+    #[test]
+    fn test_type_code_max_depth() {
+        let nested = |depth: usize| {
+            let mut ty = ValueType::U8;
+            for _ in 0..depth {
+                ty = ValueType::Array(Box::new(ty), 1);
+            }
+            ty
+        };
+
+        let ty = nested(MAX_TYPE_DEPTH);
+        assert_eq!(ValueType::from_type_code(&ty.type_code()).unwrap().0, ty);
+
+        assert!(matches!(
+            ValueType::from_type_code(&nested(MAX_TYPE_DEPTH + 1).type_code()),
+            Err(TypeError::InvalidTypeCode { position, .. }) if position == MAX_TYPE_DEPTH * 9
+        ));
+
+        // Deeply nested input is rejected without exhausting the stack.
+        let mut code = Vec::new();
+        for _ in 0..200_000 {
+            code.push(0x07);
+            code.extend_from_slice(&1u64.to_le_bytes());
+        }
+        code.push(0x01);
+        assert!(matches!(
+            ValueType::from_type_code(&code),
+            Err(TypeError::InvalidTypeCode { .. })
+        ));
+    }
+
+    #[test]
+    fn test_encode_versioned() {
+        let values = [
+            Value::Bit(true),
+            Value::U32(0x1234_5678),
+            Value::uint_from_le_bytes(12, &[0xff, 0x0a]).unwrap(),
+            Value::from([[1u8, 2], [3, 4], [5, 6]]),
+            Value::Struct(vec![Value::U16(7), Value::from([true, false, true])]),
+        ];
+
+        for value in values {
+            let bytes = value.encode_versioned();
+            assert_eq!(bytes[0], VALUE_ENCODING_VERSION);
+            assert_eq!(Value::decode_versioned(&bytes).unwrap(), value);
+        }
+
+        assert_eq!(
+            Value::U16(0x0201).encode_versioned(),
+            vec![VALUE_ENCODING_VERSION, 0x02, 0x01, 0x02]
+        );
+    }
+
+    #[test]
+    fn test_decode_versioned_invalid() {
+        let mut bytes = Value::U32(1).encode_versioned();
+        bytes[0] = VALUE_ENCODING_VERSION + 1;
+        assert!(matches!(
+            Value::decode_versioned(&bytes),
+            Err(TypeError::UnsupportedVersion { version }) if version == VALUE_ENCODING_VERSION + 1
+        ));
+
+        let mut bytes = Value::U32(1).encode_versioned();
+        bytes.pop();
+        assert!(matches!(
+            Value::decode_versioned(&bytes),
+            Err(TypeError::InvalidLength {
+                expected: 4,
+                actual: 3,
+                ..
+            })
+        ));
+
+        assert!(matches!(
+            Value::decode_versioned(&[VALUE_ENCODING_VERSION, 0xff]),
+            Err(TypeError::InvalidTypeCode { position: 1, .. })
+        ));
+        assert!(Value::decode_versioned(&[]).is_err());
+
+        // An array of zero-width elements.
+        let mut bytes = vec![VALUE_ENCODING_VERSION, 0x07];
+        bytes.extend_from_slice(&5u64.to_le_bytes());
+        bytes.push(0x06);
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        assert!(matches!(
+            Value::decode_versioned(&bytes),
+            Err(TypeError::InvalidTypeCode { position: 1, .. })
+        ));
+
+        // An array whose length in bits overflows.
+        let mut bytes = vec![VALUE_ENCODING_VERSION, 0x07];
+        bytes.extend_from_slice(&(u64::MAX / 2).to_le_bytes());
+        bytes.push(0x05);
+        assert!(matches!(
+            Value::decode_versioned(&bytes),
+            Err(TypeError::InvalidTypeCode { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_field_bytes() {
+        let bytes = Value::U64(0x0102_0304_0506_0708)
+            .to_field_bytes(32)
+            .unwrap();
+
+        assert_eq!(bytes.len(), 32);
+        assert!(bytes[..24].iter().all(|b| *b == 0));
+        assert_eq!(bytes[24..], [1, 2, 3, 4, 5, 6, 7, 8]);
+
+        assert_eq!(
+            Value::U16(0xabcd).to_field_bytes(2).unwrap(),
+            vec![0xab, 0xcd]
+        );
+        assert_eq!(
+            Value::Uint(vec![true; 12]).to_field_bytes(3).unwrap(),
+            vec![0x00, 0x0f, 0xff]
+        );
+    }
+
+    #[test]
+    fn test_to_field_bytes_too_wide() {
+        assert!(matches!(
+            Value::U128(1).to_field_bytes(8),
+            Err(TypeError::InvalidLength {
+                expected: 8,
+                actual: 16,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Value::Bit(true).to_field_bytes(32),
+            Err(TypeError::NotInteger { .. })
+        ));
+    }
+
+    #[test]
+    fn test_interleave() {
+        let lanes = [
+            Value::U8(0b0000_0001),
+            Value::U8(0b0000_0010),
+            Value::U8(0b1000_0000),
+            Value::U8(0b1111_1111),
+        ];
+
+        let sliced = Value::interleave(&lanes).unwrap();
+        assert_eq!(sliced.value_type(), ValueType::new_array::<bool>(32));
+
+        let bits: Vec<bool> = sliced.clone().into_iter_lsb0().collect();
+        assert_eq!(
+            bits[..8],
+            [true, false, false, true, false, true, false, true]
+        );
+        assert_eq!(bits[28..], [false, false, true, true]);
+
+        assert_eq!(sliced.deinterleave(4, &ValueType::U8).unwrap(), lanes);
+    }
+
+    #[test]
+    fn test_interleave_invalid() {
+        assert!(matches!(
+            Value::interleave(&[Value::U8(1), Value::U16(1)]),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::interleave(&[]),
+            Err(TypeError::ZeroLengthArray)
+        ));
+        assert!(matches!(
+            Value::from([true; 8]).deinterleave(2, &ValueType::U8),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_bin_repr_try_from_ref() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u32>().to_inner();
+        let b = builder.add_input::<u8>().to_inner();
+
+        let reprs: Vec<BinaryRepr> = vec![a.into(), b.into()];
+
+        let a_ref: &U32 = (&reprs[0]).try_into().unwrap();
+        assert_eq!(a_ref.nodes(), a.nodes());
+
+        let a_copy = U32::try_from(&reprs[0]).unwrap();
+        assert_eq!(a_copy.nodes(), a.nodes());
+
+        assert!(matches!(
+            <&U32>::try_from(&reprs[1]),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_value_type_ord() {
+        let mut types = vec![
+            ValueType::Struct(vec![ValueType::U8]),
+            ValueType::new_array::<u8>(4),
+            ValueType::U32,
+            ValueType::Uint(8),
+            ValueType::new_array::<bool>(8),
+            ValueType::Bit,
+            ValueType::new_array::<u8>(2),
+            ValueType::Struct(vec![ValueType::Bit, ValueType::U16]),
+            ValueType::U8,
+            ValueType::Uint(12),
+        ];
+
+        types.sort();
+
+        assert_eq!(
+            types,
+            vec![
+                ValueType::Bit,
+                ValueType::U8,
+                ValueType::Uint(8),
+                ValueType::Uint(12),
+                ValueType::U32,
+                ValueType::new_array::<bool>(8),
+                ValueType::new_array::<u8>(2),
+                ValueType::new_array::<u8>(4),
+                ValueType::Struct(vec![ValueType::Bit, ValueType::U16]),
+                ValueType::Struct(vec![ValueType::U8]),
+            ]
+        );
+
+        let map: std::collections::BTreeMap<ValueType, usize> =
+            types.iter().cloned().zip(0..).collect();
+        assert_eq!(map.keys().next(), Some(&ValueType::Bit));
+    }
+
+    #[test]
+    fn test_to_bits_padded() {
+        let bits = Value::Bit(true).to_bits_padded(BitOrder::Lsb0);
+        assert_eq!(
+            bits,
+            [true, false, false, false, false, false, false, false]
+        );
+
+        let bits = Value::from([true; 9]).to_bits_padded(BitOrder::Msb0);
+        assert_eq!(bits.len(), 16);
+        assert!(bits[..9].iter().all(|bit| *bit));
+        assert!(!bits[9..].iter().any(|bit| *bit));
+
+        let bits = Value::U16(1).to_bits_padded(BitOrder::Msb0);
+        assert_eq!(bits.len(), 16);
+        assert!(bits[15]);
+    }
+
+    #[test]
+    fn test_typed_repr() {
+        let builder = CircuitBuilder::new();
+        let repr: BinaryRepr = builder.add_input::<[u8; 4]>().into();
+
+        let ty = ValueType::new::<[u8; 4]>();
+        let typed = TypedRepr::new(repr.clone(), ty.clone()).unwrap();
+        assert_eq!(typed.ty(), &ty);
+        assert_eq!(typed.repr().len(), 32);
+
+        let bits: Vec<bool> = Value::from([1u8, 2, 3, 4]).into_iter_lsb0().collect();
+        assert_eq!(typed.decode(&bits).unwrap(), Value::from([1u8, 2, 3, 4]));
+        assert!(matches!(
+            typed.decode(&bits[..8]),
+            Err(TypeError::InvalidLength { .. })
+        ));
+
+        assert!(matches!(
+            TypedRepr::new(repr, ValueType::U32),
+            Err(TypeError::InvalidRepr { .. })
+        ));
+    }
+
+    #[test]
+    fn test_leading_trailing_zeros() {
+        assert_eq!(Value::U8(0).leading_zeros().unwrap(), 8);
+        assert_eq!(Value::U8(0).trailing_zeros().unwrap(), 8);
+        assert_eq!(Value::U8(1).leading_zeros().unwrap(), 7);
+        assert_eq!(Value::U8(1).trailing_zeros().unwrap(), 0);
+        assert_eq!(Value::U8(u8::MAX).leading_zeros().unwrap(), 0);
+        assert_eq!(Value::U8(u8::MAX).trailing_zeros().unwrap(), 0);
+
+        assert_eq!(Value::U64(0).leading_zeros().unwrap(), 64);
+        assert_eq!(Value::U64(0).trailing_zeros().unwrap(), 64);
+        assert_eq!(Value::U64(1).leading_zeros().unwrap(), 63);
+        assert_eq!(Value::U64(1 << 40).trailing_zeros().unwrap(), 40);
+        assert_eq!(Value::U64(u64::MAX).leading_zeros().unwrap(), 0);
+
+        let v = Value::Uint(vec![false, true, false, false, false]);
+        assert_eq!(v.leading_zeros().unwrap(), 3);
+        assert_eq!(v.trailing_zeros().unwrap(), 1);
+
+        assert!(matches!(
+            Value::Bit(false).leading_zeros(),
+            Err(TypeError::NotInteger { .. })
+        ));
+        assert!(matches!(
+            Value::from([0u8; 2]).trailing_zeros(),
+            Err(TypeError::NotInteger { .. })
+        ));
+    }
+
+    #[test]
+    fn test_splice() {
+        let mut value = Value::from([0u8; 8]);
+        value.splice(2, &Value::from([1u8, 2])).unwrap();
+
+        assert_eq!(value, Value::from([0u8, 0, 1, 2, 0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn test_splice_invalid() {
+        let mut value = Value::from([0u8; 8]);
+
+        assert!(matches!(
+            value.splice(7, &Value::from([1u8, 2])),
+            Err(TypeError::InvalidLength {
+                expected: 8,
+                actual: 9,
+                ..
+            })
+        ));
+        assert!(matches!(
+            value.splice(0, &Value::from([1u16, 2])),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            value.splice(0, &Value::U8(1)),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            value.splice(usize::MAX, &Value::from([1u8, 2])),
+            Err(TypeError::InvalidLength {
+                expected: 8,
+                actual: usize::MAX,
+                ..
+            })
+        ));
+        assert_eq!(value, Value::from([0u8; 8]));
+    }
+
+    #[test]
+    fn test_into_bits_vec() {
+        assert_eq!(
+            Value::U8(0b1010_0000).into_bits_vec(BitOrder::Lsb0),
+            vec![false, false, false, false, false, true, false, true]
+        );
+        assert_eq!(
+            Value::U8(0b1010_0000).into_bits_vec(BitOrder::Msb0),
+            vec![true, false, true, false, false, false, false, false]
+        );
+    }
+
+    #[test]
+    fn test_from_bin_repr_invalid_length_path() {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_input::<u8>().to_inner();
+        let b = builder.add_input::<u16>().to_inner();
+
+        // The inner arrays are ragged, so decoding the second element of the first inner
+        // array is given too few bits.
+        let repr = BinaryRepr::Array(vec![
+            BinaryRepr::Array(vec![a.into(), b.into()]),
+            BinaryRepr::Array(vec![b.into(), a.into()]),
+        ]);
+
+        let err = repr.from_bin_repr(&[false; 48]).unwrap_err();
+
+        assert!(matches!(
+            &err,
+            TypeError::InvalidLength {
+                expected: 16,
+                actual: 8,
+                path,
+            } if path == &[0, 1]
+        ));
+        assert!(err.to_string().ends_with("at path [0, 1]"));
+    }
+
+    #[test]
+    fn test_from_bin_repr_partial() {
+        let builder = CircuitBuilder::new();
+        let repr: BinaryRepr = builder.add_input::<[u8; 4]>().to_inner().into();
+        let value = Value::from([1u8, 2, 3, 4]);
+        let bits: Vec<bool> = value.clone().into_iter_lsb0().collect();
+
+        let (partial, err) = repr.from_bin_repr_partial(&bits[..24]);
+        assert_eq!(partial, Some(Value::from([1u8, 2, 3])));
+        assert!(matches!(
+            err,
+            Some(TypeError::InvalidLength {
+                expected: 32,
+                actual: 24,
+                ..
+            })
+        ));
+
+        // Incomplete leaves are dropped.
+        let (partial, err) = repr.from_bin_repr_partial(&bits[..20]);
+        assert_eq!(partial, Some(Value::from([1u8, 2])));
+        assert!(err.is_some());
+
+        let (partial, err) = repr.from_bin_repr_partial(&bits[..4]);
+        assert_eq!(partial, None);
+        assert!(err.is_some());
+
+        let (partial, err) = repr.from_bin_repr_partial(&bits);
+        assert_eq!(partial, Some(value));
+        assert!(err.is_none());
+
+        // Malformed representations are reported rather than decoded.
+        let ragged = BinaryRepr::Array(vec![
+            builder.add_input::<u8>().to_inner().into(),
+            builder.add_input::<u16>().to_inner().into(),
+        ]);
+        let (partial, err) = ragged.from_bin_repr_partial(&[false; 24]);
+        assert_eq!(partial, None);
+        assert!(matches!(
+            err,
+            Some(TypeError::InvalidLength { path, .. }) if path == [1]
+        ));
+
+        // Nested arrays keep the complete leaves of a partial element.
+        let repr: BinaryRepr = builder.add_input::<[[u8; 2]; 2]>().to_inner().into();
+        let value = Value::from([[1u8, 2], [3, 4]]);
+        let bits: Vec<bool> = value.into_iter_lsb0().collect();
+        let (partial, _) = repr.from_bin_repr_partial(&bits[..24]);
+        assert_eq!(
+            partial,
+            Some(Value::Array(vec![
+                Value::from([1u8, 2]),
+                Value::from([3u8])
+            ]))
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_from_bin_repr_par() {
+        let builder = CircuitBuilder::new();
+        let ty = ValueType::Array(Box::new(ValueType::new_array::<u32>(2)), 10_000);
+        let repr = builder.add_input_by_type(ty.clone());
+
+        let mut rng = rand::thread_rng();
+        let value = Value::random(&mut rng, &ty);
+        let bits: Vec<bool> = value.clone().into_iter_lsb0().collect();
+
+        let BinaryRepr::Array(elems) = &repr else {
+            unreachable!()
+        };
+
+        let par = BinaryRepr::decode_elements_par(elems, &bits).unwrap();
+        let seq = BinaryRepr::decode_elements(elems, &bits).unwrap();
+
+        assert_eq!(par, seq);
+        assert_eq!(Value::Array(par), value);
+        assert_eq!(repr.from_bin_repr(&bits).unwrap(), value);
+
+        // Short arrays are decoded sequentially.
+        let ty = ValueType::new_array::<u32>(PAR_DECODE_MIN_ELEMENTS - 1);
+        let repr = builder.add_input_by_type(ty.clone());
+        let value = Value::random(&mut rng, &ty);
+        let bits: Vec<bool> = value.clone().into_iter_lsb0().collect();
+        assert_eq!(repr.from_bin_repr(&bits).unwrap(), value);
+    }
+
+    #[test]
+    fn test_sorted() {
+        assert_eq!(
+            Value::from([3u8, 1, 255, 0, 1]).sorted().unwrap(),
+            Value::from([0u8, 1, 1, 3, 255])
+        );
+
+        let nested = Value::from([[2u16, 1], [1, 9], [2, 0], [1, 2]]);
+        assert_eq!(
+            nested.sorted().unwrap(),
+            Value::from([[1u16, 2], [1, 9], [2, 0], [2, 1]])
+        );
+
+        assert!(matches!(
+            Value::Array(vec![Value::U8(1), Value::U16(0)]).sorted(),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::from([true, false]).sorted(),
+            Err(TypeError::NotInteger { .. })
+        ));
+        assert!(matches!(
+            Value::U8(1).sorted(),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_clamp() {
+        let (min, max) = (Value::U16(10), Value::U16(1000));
+
+        assert_eq!(Value::U16(3).clamp(&min, &max).unwrap(), Value::U16(10));
+        assert_eq!(Value::U16(500).clamp(&min, &max).unwrap(), Value::U16(500));
+        assert_eq!(
+            Value::U16(4000).clamp(&min, &max).unwrap(),
+            Value::U16(1000)
+        );
+
+        let value = Value::from([1u8, 50, 200]);
+        assert_eq!(
+            value.clamp(&Value::U8(10), &Value::U8(100)).unwrap(),
+            Value::from([10u8, 50, 100])
+        );
+        assert_eq!(
+            value
+                .clamp(&Value::from([0u8, 60, 0]), &Value::from([0u8, 100, 100]))
+                .unwrap(),
+            Value::from([0u8, 60, 100])
+        );
+    }
+
+    #[test]
+    fn test_clamp_invalid() {
+        assert!(matches!(
+            Value::U16(3).clamp(&Value::U8(1), &Value::U16(5)),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::from([1u8, 2]).clamp(&Value::from([0u8; 3]), &Value::U8(5)),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::Bit(true).clamp(&Value::Bit(false), &Value::Bit(true)),
+            Err(TypeError::NotInteger { .. })
+        ));
+    }
+
+    #[test]
+    fn test_int_to_bits() {
+        let value = Value::U32(0x8000_0001 | 0b110);
+
+        for order in [BitOrder::Lsb0, BitOrder::Msb0] {
+            let bits = value.int_to_bits(order).unwrap();
+            assert_eq!(bits.value_type(), ValueType::new_array::<bool>(32));
+            assert_eq!(
+                Value::bits_to_int(&bits, &ValueType::U32, order).unwrap(),
+                value
+            );
+        }
+
+        let Value::Array(bits) = value.int_to_bits(BitOrder::Msb0).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(bits[0], Value::Bit(true));
+        assert_eq!(
+            bits[29..],
+            [Value::Bit(true), Value::Bit(true), Value::Bit(true)]
+        );
+    }
+
+    #[test]
+    fn test_bits_to_int_invalid() {
+        assert!(matches!(
+            Value::bits_to_int(&Value::from([true; 8]), &ValueType::U16, BitOrder::Lsb0),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::bits_to_int(&Value::from([1u8; 8]), &ValueType::U8, BitOrder::Lsb0),
+            Err(TypeError::UnexpectedType { .. })
+        ));
+        assert!(matches!(
+            Value::bits_to_int(&Value::Bit(true), &ValueType::Bit, BitOrder::Lsb0),
+            Err(TypeError::NotInteger { .. })
+        ));
+        assert!(matches!(
+            Value::Bit(true).int_to_bits(BitOrder::Lsb0),
+            Err(TypeError::NotInteger { .. })
+        ));
+    }
+
+    #[test]
+    fn test_named_type() {
+        let named = ValueType::Named {
+            name: "ms".to_string(),
+            inner: Box::new(ValueType::U64),
+        };
+
+        assert_eq!(named.len(), ValueType::U64.len());
+        assert_eq!(named.unnamed(), &ValueType::U64);
+        assert!(named.is_scalar());
+        assert!(named.matches(&ValueType::U64));
+        assert!(ValueType::U64.matches(&named));
+        assert_ne!(named, ValueType::U64);
+        assert!(named > ValueType::U64);
+        assert!(named < ValueType::U128);
+        assert_eq!(named.to_string(), "Named<ms, U64>");
+
+        let code = named.type_code();
+        assert_eq!(
+            ValueType::from_type_code(&code).unwrap(),
+            (named, code.len())
+        );
+    }
+
+    #[test]
+    fn test_named_value_transparent() {
+        let ty = ValueType::Named {
+            name: "ms".to_string(),
+            inner: Box::new(ValueType::U64),
+        };
+        let value = Value::Named {
+            name: "ms".to_string(),
+            inner: Box::new(Value::U64(42)),
+        };
+
+        assert_eq!(value.value_type(), ty);
+        assert_eq!(value.bit_len(), 64);
+        assert_eq!(
+            value.clone().into_iter_lsb0().collect::<Vec<_>>(),
+            Value::U64(42).into_iter_lsb0().collect::<Vec<_>>()
+        );
+
+        let builder = CircuitBuilder::new();
+        let repr = builder.add_input_by_type(ty.clone());
+        let bits: Vec<bool> = value.clone().into_iter_lsb0().collect();
+        assert!(ty.validate_repr(&repr).is_ok());
+        assert_eq!(repr.from_bin_repr(&bits).unwrap(), Value::U64(42));
+
+        let typed = TypedRepr::new(repr, ty).unwrap();
+        assert_eq!(typed.decode(&bits).unwrap(), value);
+
+        assert_eq!(value.to_string(), "Named<ms>(U64(42))");
+        assert_eq!(
+            Value::from_str_compact(&value.to_string_compact()).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn test_cached_type() {
+        let nested = |x: u128, len: usize| Value::Array(vec![Value::from([x; 4]); len]);
+        let value = nested(0, 16);
+
+        let ty = value.cached_type();
+        assert_eq!(*ty, value.value_type());
+        assert!(Arc::ptr_eq(&ty, &value.cached_type()));
+        assert!(Arc::ptr_eq(&ty, &nested(1, 16).cached_type()));
+        assert!(!Arc::ptr_eq(&ty, &nested(1, 8).cached_type()));
+
+        let value = Value::Struct(vec![Value::U8(1), Value::from([0u32; 2])]);
+        assert_eq!(*value.cached_type(), value.value_type());
+
+        let value = Value::Named {
+            name: "ms".to_string(),
+            inner: Box::new(nested(0, 16)),
+        };
+        let ty = value.cached_type();
+        assert_eq!(*ty, value.value_type());
+        assert!(Arc::ptr_eq(&ty, &value.cached_type()));
+    }
+
+    #[test]
+    fn test_cached_type_bounded() {
+        for width in 1..=2 * MAX_CACHED_TYPES {
+            let value = Value::Uint(vec![false; width]);
+            assert_eq!(*value.cached_type(), ValueType::Uint(width));
+        }
+        assert!(TYPE_CACHE.with(|cache| cache.borrow().len()) <= MAX_CACHED_TYPES);
+
+        let value = Value::from([[1u8; 2]; 3]);
+        assert_eq!(*value.cached_type(), value.value_type());
+    }
+
+    #[test]
+    fn test_assert_value_eq() {
+        assert_value_eq!(Value::from([1u8, 2]), Value::from([1u8, 2]));
+    }
+
+    #[test]
+    #[should_panic(expected = "differing bits: 2\n[1].1: U16(1) != U16(2)")]
+    fn test_assert_value_eq_mismatch() {
+        assert_value_eq!(
+            Value::splat(Value::Struct(vec![Value::U8(1), Value::U16(1)]), 2).unwrap(),
+            Value::Array(vec![
+                Value::Struct(vec![Value::U8(1), Value::U16(1)]),
+                Value::Struct(vec![Value::U8(1), Value::U16(2)]),
+            ])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unexpected type")]
+    fn test_assert_value_eq_type_mismatch() {
+        assert_value_eq!(Value::U8(1), Value::U16(1));
+    }
+
+    #[test]
+    fn test_value_macro() {
+        assert_eq!(value!(42u32), Value::U32(42));
+        assert_eq!(value!(true), Value::Bit(true));
+        assert_eq!(
+            value!([1u8, 2u8, 3u8]),
+            Value::Array(vec![Value::U8(1), Value::U8(2), Value::U8(3)])
+        );
+        assert_eq!(
+            value!([[1u16, 2], [3, 4]]),
+            Value::Array(vec![
+                Value::Array(vec![Value::U16(1), Value::U16(2)]),
+                Value::Array(vec![Value::U16(3), Value::U16(4)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_value_struct() {
+        define_value_struct!(
+            #[derive(Debug, Clone, Copy, PartialEq)]
+            struct Header {
+                version: u8,
+                flags: u32,
+                nonce: [u8; 12],
+            }
+        );
+
+        assert_eq!(Header::LEN, 8 + 32 + 12 * 8);
+        assert_eq!(
+            ValueType::new::<Header>(),
+            ValueType::Struct(vec![
+                ValueType::U8,
+                ValueType::U32,
+                ValueType::new_array::<u8>(12)
+            ])
+        );
+
+        let header = Header {
+            version: 1,
+            flags: 0xdeadbeef,
+            nonce: [42u8; 12],
+        };
+
+        let value: Value = header.into();
+        assert_eq!(value.bit_len(), Header::LEN);
+        assert_eq!(Header::try_from(value.clone()).unwrap(), header);
+        assert!(Header::try_from(Value::from([1u8; 17])).is_err());
+
+        let builder = CircuitBuilder::new();
+        let repr: BinaryRepr = builder.add_input::<Header>().into();
+        assert_eq!(repr.len(), Header::LEN);
+        assert_eq!(repr.value_type(), ValueType::new::<Header>());
+
+        let bits: Vec<bool> = value.clone().into_iter_lsb0().collect();
+        assert_eq!(repr.from_bin_repr(&bits).unwrap(), value);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary_value() {
+        use arbitrary::{Arbitrary, Unstructured};
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let bytes: Vec<u8> = (0..1024).map(|_| rng.gen()).collect();
+            let mut u = Unstructured::new(&bytes);
+
+            let ty = ValueType::arbitrary(&mut u).unwrap();
+            let value = Value::arbitrary_with_type(&mut u, &ty).unwrap();
+
+            assert_eq!(value.value_type(), ty);
+        }
     }
 }
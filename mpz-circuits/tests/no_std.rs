@@ -0,0 +1,28 @@
+//! Checks that `mpz_circuits::types` is usable from a `no_std` crate.
+//!
+//! Tests are built with default features, so this only checks that the API does not
+//! rely on the std prelude. Building the crate itself without `std` is checked in CI
+//! with `cargo check --no-default-features` against a no_std target.
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{boxed::Box, vec::Vec};
+
+use mpz_circuits::types::{BinaryLength, BitOrder, StaticValueType, Value, ValueType};
+
+#[test]
+fn test_no_std_types() {
+    let ty = <[u8; 2]>::value_type();
+    assert_eq!(ty, ValueType::Array(Box::new(ValueType::U8), 2));
+    assert_eq!(<[u8; 2]>::LEN, 16);
+
+    let value = Value::from([1u8, 2]);
+    assert_eq!(value.value_type(), ty);
+
+    let bits: Vec<bool> = (0..16).map(|i| i == 0 || i == 9).collect();
+    assert_eq!(Value::from_bits(&ty, bits, BitOrder::Lsb0).unwrap(), value);
+
+    let code = ty.type_code();
+    assert_eq!(ValueType::from_type_code(&code).unwrap(), (ty, code.len()));
+}